@@ -0,0 +1,460 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for authoring CEA-608 captions from a list of timed cues
+
+use std::time::Duration;
+
+use crate::tables::{Channel, Code, Control, ControlCode, Field, PreambleAddressCode};
+use crate::{Cea608Writer, Mode, WriterError};
+
+/// The maximum number of columns in a caption row
+const MAX_COLUMNS: usize = 32;
+/// The maximum number of rows a cue will be wrapped into
+const MAX_ROWS: usize = 4;
+/// The row that captions are placed on or above
+const BASE_ROW: u8 = 14;
+
+/// A timed caption cue, e.g. from an SRT file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    /// The time the cue should be displayed
+    pub start: Duration,
+    /// The time the cue should be removed from the display
+    pub end: Duration,
+    /// The text of the cue.  `\n` forces a new line.
+    pub text: String,
+}
+
+/// Produce a timed sequence of CEA-608 byte pairs displaying the provided cues on CC1.
+///
+/// The returned byte pairs are tagged with the frame number they should be sent in, where frames
+/// are counted from a time of zero at `fps` frames per second.  Frames without a byte pair should
+/// be filled with padding.  Cues must be ordered by their start time.
+///
+/// Each cue is wrapped to at most 4 rows of 32 columns, placed at the bottom of the screen.  In
+/// Pop-On and Paint-On mode, the rows are centered.  In the Roll-Up modes, each row is rolled up
+/// from the bottom row of the screen.
+///
+/// In Pop-On mode, the caption is loaded ahead of time so that the End Of Caption command is sent
+/// at the start time of the cue.  In the other modes, the caption is sent from the start time of
+/// the cue.  The display is erased at the end time of a cue unless, in Pop-On mode, the following
+/// cue has already replaced it.  All commands are doubled.  Special and extended characters are
+/// transmitted as control codes but are sent once.
+///
+/// Characters that cannot be represented in CEA-608 are skipped.
+pub fn from_cues(cues: &[Cue], mode: Mode, fps: f64) -> Vec<(u64, [u8; 2])> {
+    let to_frame = |time: Duration| (time.as_secs_f64() * fps).round() as u64;
+    let mut ret = vec![];
+    let mut next_free = 0;
+    let mut pending_erase = None::<u64>;
+
+    for cue in cues {
+        let start = to_frame(cue.start);
+        let block = cue_pairs(&cue.text, mode);
+
+        let mut erase = pending_erase.take();
+        if mode == Mode::PopOn {
+            // the new caption will replace the current one so no need to erase it
+            erase = erase.filter(|&erase| erase < start);
+        }
+        if let Some(erase_frame) = erase {
+            let block_start = block_start(start, block.len(), mode).max(next_free);
+            let erase_frame = erase_frame.max(next_free);
+            if erase_frame + 2 <= block_start {
+                push_erase(&mut ret, erase_frame);
+                next_free = erase_frame + 2;
+                erase = None;
+            }
+        }
+
+        let n_pairs = block.len() + if erase.is_some() { 2 } else { 0 };
+        let mut frame = block_start(start, n_pairs, mode).max(next_free);
+        for (i, data) in block.iter().enumerate() {
+            if let Some(erase_frame) = erase {
+                // don't split a doubled control code and always erase before the caption is shown
+                let boundary = i == 0 || block[i - 1] != *data;
+                let last = i + 2 >= block.len();
+                if boundary && (erase_frame <= frame || last || mode != Mode::PopOn) {
+                    push_erase(&mut ret, frame);
+                    frame += 2;
+                    erase = None;
+                }
+            }
+            ret.push((frame, *data));
+            frame += 1;
+        }
+        next_free = frame;
+        pending_erase = Some(to_frame(cue.end));
+    }
+
+    if let Some(erase_frame) = pending_erase {
+        push_erase(&mut ret, erase_frame.max(next_free));
+    }
+
+    ret
+}
+
+/// Produce a timed sequence of CEA-608 byte pairs containing the characters of `text` on CC1.
+///
+/// At most one byte pair, and therefore at most two characters, is sent per frame.  The first
+/// byte pair is sent in frame `start` and each following byte pair in the next frame.  Special and
+/// extended characters take up a whole byte pair and extended characters additionally require a
+/// replacement character (see [`Code::needs_backspace`]).  Frames without a byte pair should be
+/// filled with padding.  No control codes are added, the caller is responsible for setting the
+/// mode and position of the text.
+///
+/// Characters that cannot be represented in CEA-608 are skipped.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::author::pace_text;
+/// assert_eq!(pace_text("Hi!", 10), [(10, [0xc8, 0xe9]), (11, [0xa1, 0x80])]);
+/// ```
+pub fn pace_text(text: &str, start: u64) -> Vec<(u64, [u8; 2])> {
+    let mut writer = Cea608Writer::default();
+    for c in text.chars() {
+        if writer.push_char(c, Channel::ONE).is_err() {
+            warn!("Skipping unrepresentable character {c:?}");
+        }
+    }
+    (start..).zip(writer.drain_all()).collect()
+}
+
+/// Produce the [`Code`]s for a single roll-up caption line on the provided [`Field`] and
+/// [`Channel`].
+///
+/// The codes are, in order: the control code selecting `mode`, a preamble placing the cursor at
+/// the start of the bottom row, the characters of `text` and a carriage return scrolling the line
+/// up.  All commands are doubled, special and extended characters in `text` are not.  The codes
+/// can be pushed into a [`Cea608Writer`] which handles combining the characters into byte pairs.
+///
+/// Returns [`WriterError::WouldOverflow`] with the number of excess characters if `text` does not
+/// fit in a single row of 32 columns, and [`WriterError::UnrepresentableChar`] if `text` contains
+/// a character that cannot be represented.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Cea608Writer, Mode, author::rollup_line, tables::{Channel, Field}};
+/// let codes = rollup_line(Field::ONE, Channel::ONE, Mode::RollUp2, "Hi").unwrap();
+/// let mut writer = Cea608Writer::default();
+/// for code in codes {
+///     writer.push(code);
+/// }
+/// assert_eq!(writer.drain_all().len(), 7);
+/// ```
+pub fn rollup_line(
+    field: Field,
+    channel: Channel,
+    mode: Mode,
+    text: &str,
+) -> Result<Vec<Code>, WriterError> {
+    let len = text.chars().count();
+    if len > MAX_COLUMNS {
+        return Err(WriterError::WouldOverflow(len - MAX_COLUMNS));
+    }
+    let control = |control| Code::Control(ControlCode::new(field, channel, control));
+    let (preamble, _) = PreambleAddressCode::for_position(BASE_ROW, 0).unwrap();
+    let mut codes = vec![];
+    for c in [mode.to_control(), Control::PreambleAddress(preamble)] {
+        codes.extend([control(c), control(c)]);
+    }
+    for c in text.chars() {
+        codes.push(Code::from_char(c, channel).ok_or(WriterError::UnrepresentableChar(c))?);
+    }
+    codes.extend([control(Control::CarriageReturn); 2]);
+    Ok(codes)
+}
+
+fn block_start(start: u64, n_pairs: usize, mode: Mode) -> u64 {
+    if mode == Mode::PopOn {
+        // the first of the doubled End Of Caption commands is placed on the start frame
+        (start + 2).saturating_sub(n_pairs as u64)
+    } else {
+        start
+    }
+}
+
+fn push_erase(ret: &mut Vec<(u64, [u8; 2])>, frame: u64) {
+    let mut data = [0; 2];
+    control(Control::EraseDisplayedMemory).write_into(&mut data);
+    ret.push((frame, data));
+    ret.push((frame + 1, data));
+}
+
+fn control(control: Control) -> Code {
+    Code::Control(ControlCode::new(Field::ONE, Channel::ONE, control))
+}
+
+fn push_doubled(writer: &mut Cea608Writer, control: Control) {
+    writer.push(self::control(control));
+    writer.push(self::control(control));
+}
+
+fn cue_pairs(text: &str, mode: Mode) -> Vec<[u8; 2]> {
+    let lines = wrap(text);
+    let mut writer = Cea608Writer::default();
+
+    push_doubled(&mut writer, mode.to_control());
+    if mode == Mode::PopOn {
+        push_doubled(&mut writer, Control::EraseNonDisplayedMemory);
+    }
+
+    let first_row = BASE_ROW + 1 - lines.len() as u8;
+    for (i, line) in lines.iter().enumerate() {
+        let (row, column) = if mode.is_rollup() {
+            push_doubled(&mut writer, Control::CarriageReturn);
+            (BASE_ROW, 0)
+        } else {
+            let len = line.chars().count();
+            (first_row + i as u8, ((MAX_COLUMNS - len) / 2) as u8)
+        };
+        let (preamble, tab_offset) = PreambleAddressCode::for_position(row, column).unwrap();
+        push_doubled(&mut writer, Control::PreambleAddress(preamble));
+        if let Some(tab_offset) = tab_offset {
+            push_doubled(&mut writer, tab_offset);
+        }
+        for c in line.chars() {
+            if let Some(code) = Code::from_char(c, Channel::ONE) {
+                writer.push(code);
+            } else {
+                warn!("Skipping unrepresentable character {c:?}");
+            }
+        }
+    }
+
+    if mode == Mode::PopOn {
+        push_doubled(&mut writer, Control::EndOfCaption);
+    }
+
+    writer.drain_all()
+}
+
+fn wrap(text: &str) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_len = 0;
+        for word in paragraph.split_whitespace() {
+            let mut word = word.chars().collect::<Vec<_>>();
+            if line_len > 0 && line_len + 1 + word.len() <= MAX_COLUMNS {
+                line.push(' ');
+                line_len += 1;
+            } else if line_len > 0 {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+            while word.len() > MAX_COLUMNS {
+                let rest = word.split_off(MAX_COLUMNS);
+                lines.push(word.into_iter().collect());
+                word = rest;
+            }
+            line_len += word.len();
+            line.extend(word);
+        }
+        if line_len > 0 {
+            lines.push(line);
+        }
+    }
+    if lines.len() > MAX_ROWS {
+        warn!("Cue has {} rows, truncating to {MAX_ROWS}", lines.len());
+        lines.truncate(MAX_ROWS);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::coalesce::{coalesce_text, Coalesced};
+    use crate::tests::*;
+    use crate::{Cea608, Cea608State};
+
+    fn decode(pairs: &[(u64, [u8; 2])]) -> Vec<(u64, Cea608)> {
+        let mut state = Cea608State::default();
+        let mut last_frame = None;
+        let mut ret = vec![];
+        for (frame, data) in pairs {
+            if let Some(last_frame) = last_frame {
+                assert!(*frame > last_frame);
+                if *frame > last_frame + 1 {
+                    state.decode([0x80, 0x80]).unwrap();
+                }
+            }
+            last_frame = Some(*frame);
+            if let Some(cea608) = state.decode(*data).unwrap() {
+                ret.push((*frame, cea608));
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn author_wrap() {
+        test_init_log();
+        assert_eq!(wrap("Hello world"), ["Hello world"]);
+        assert_eq!(wrap("Hello\nworld"), ["Hello", "world"]);
+        let long = "a".repeat(40);
+        assert_eq!(wrap(&long), ["a".repeat(32), "a".repeat(8)]);
+        assert_eq!(
+            wrap("one two three four five six seven eight nine ten eleven"),
+            ["one two three four five six", "seven eight nine ten eleven"]
+        );
+        assert_eq!(wrap("1\n2\n3\n4\n5"), ["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn author_pace_text() {
+        test_init_log();
+        let pairs = pace_text("0123456789", 100);
+        assert_eq!(
+            pairs.iter().map(|(frame, _)| *frame).collect::<Vec<_>>(),
+            [100, 101, 102, 103, 104]
+        );
+        assert_eq!(pairs[0].1, [0xb0, 0x31]);
+        // an extended character needs a replacement character and its own byte pair
+        let pairs = pace_text("aÉb", 0);
+        assert_eq!(
+            pairs.iter().map(|(frame, _)| *frame).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        let mut state = Cea608State::default();
+        state.set_transcript_enabled(true);
+        state.decode([0x94, 0x29]).unwrap();
+        for (_, data) in pairs {
+            state.decode(data).unwrap();
+        }
+        assert_eq!(state.transcript(Channel::ONE), "aÉb");
+    }
+
+    #[test]
+    fn author_rollup_line() {
+        test_init_log();
+        let codes = rollup_line(Field::TWO, Channel::TWO, Mode::RollUp3, "Hello É").unwrap();
+        let mut writer = Cea608Writer::default();
+        for code in codes {
+            writer.push(code);
+        }
+        let mut state = Cea608State::default();
+        state.set_transcript_enabled(true);
+        let events = writer
+            .drain_all()
+            .into_iter()
+            .filter_map(|data| state.decode(data).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events[0], Cea608::NewMode(Channel::TWO, Mode::RollUp3));
+        assert!(
+            matches!(events[1], Cea608::Preamble(Channel::TWO, preamble) if preamble.row() == 14)
+        );
+        assert_eq!(events.last(), Some(&Cea608::CarriageReturn(Channel::TWO)));
+        assert_eq!(state.transcript(Channel::TWO), "Hello É\n");
+        assert_eq!(state.last_received_field(), Some(Field::TWO));
+
+        let long = "a".repeat(35);
+        assert_eq!(
+            rollup_line(Field::ONE, Channel::ONE, Mode::RollUp2, &long),
+            Err(WriterError::WouldOverflow(3))
+        );
+        assert_eq!(
+            rollup_line(Field::ONE, Channel::ONE, Mode::RollUp2, "€"),
+            Err(WriterError::UnrepresentableChar('€'))
+        );
+    }
+
+    #[test]
+    fn author_pop_on() {
+        test_init_log();
+        let cues = [
+            Cue {
+                start: Duration::from_secs(1),
+                end: Duration::from_secs(2),
+                text: String::from("Hello"),
+            },
+            Cue {
+                start: Duration::from_secs(3),
+                end: Duration::from_secs(4),
+                text: String::from("World"),
+            },
+        ];
+        let pairs = from_cues(&cues, Mode::PopOn, 30.0);
+        let events = decode(&pairs);
+        let eocs = events
+            .iter()
+            .filter(|(_, cea608)| matches!(cea608, Cea608::EndOfCaption(_)))
+            .map(|(frame, _)| *frame)
+            .collect::<Vec<_>>();
+        assert_eq!(eocs, [30, 90]);
+        let erases = events
+            .iter()
+            .filter(|(_, cea608)| matches!(cea608, Cea608::EraseDisplay(_)))
+            .map(|(frame, _)| *frame)
+            .collect::<Vec<_>>();
+        assert_eq!(erases, [60, 120]);
+        let text = coalesce_text(events.into_iter().map(|(_, cea608)| cea608))
+            .filter_map(|coalesced| match coalesced {
+                Coalesced::TextRun { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(text, ["Hello", "World"]);
+    }
+
+    #[test]
+    fn author_pop_on_back_to_back() {
+        test_init_log();
+        let cues = [
+            Cue {
+                start: Duration::from_millis(500),
+                end: Duration::from_millis(1100),
+                text: String::from("Hello"),
+            },
+            Cue {
+                start: Duration::from_millis(1400),
+                end: Duration::from_secs(2),
+                text: String::from("World"),
+            },
+        ];
+        let pairs = from_cues(&cues, Mode::PopOn, 30.0);
+        let events = decode(&pairs)
+            .into_iter()
+            .filter(|(_, cea608)| {
+                matches!(cea608, Cea608::EndOfCaption(_) | Cea608::EraseDisplay(_))
+            })
+            .collect::<Vec<_>>();
+        // the erase of the first cue is placed in the middle of loading the second cue
+        assert_eq!(
+            events,
+            [
+                (15, Cea608::EndOfCaption(Channel::ONE)),
+                (33, Cea608::EraseDisplay(Channel::ONE)),
+                (42, Cea608::EndOfCaption(Channel::ONE)),
+                (60, Cea608::EraseDisplay(Channel::ONE)),
+            ]
+        );
+    }
+
+    #[test]
+    fn author_roll_up() {
+        test_init_log();
+        let cues = [Cue {
+            start: Duration::from_secs(1),
+            end: Duration::from_secs(2),
+            text: String::from("Hi"),
+        }];
+        let pairs = from_cues(&cues, Mode::RollUp2, 30.0);
+        let events = decode(&pairs);
+        assert_eq!(
+            events[0],
+            (30, Cea608::NewMode(Channel::ONE, Mode::RollUp2))
+        );
+        assert_eq!(events[1], (32, Cea608::CarriageReturn(Channel::ONE)));
+        assert!(matches!(events[2], (34, Cea608::Preamble(_, preamble)) if preamble.row() == 14));
+        assert_eq!(
+            events.last(),
+            Some(&(60, Cea608::EraseDisplay(Channel::ONE)))
+        );
+    }
+}
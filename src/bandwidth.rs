@@ -0,0 +1,85 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for estimating the bandwidth required to transmit CEA-608 data
+
+use crate::tables::Code;
+use crate::Cea608Writer;
+
+/// The number of byte pairs that [`Cea608Writer::pop`] will produce for the provided [`Code`]s.
+///
+/// Consecutive single byte codes are packed into a single byte pair and codes that need a
+/// backspace (see [`Code::needs_backspace`]) require an additional replacement character.  At one
+/// byte pair per frame, this is also the number of frames needed to send `codes`.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::bandwidth::pair_count;
+/// # use cea608_types::tables::{Channel, Code};
+/// let codes = [Code::LatinCapitalA, Code::LatinCapitalB, Code::LatinCapitalC];
+/// assert_eq!(pair_count(&codes), 2);
+/// let codes = [Code::from_char('É', Channel::ONE).unwrap()];
+/// assert_eq!(pair_count(&codes), 2);
+/// ```
+pub fn pair_count(codes: &[Code]) -> usize {
+    let mut writer = Cea608Writer::default();
+    for code in codes {
+        writer.push(*code);
+    }
+    let mut n_pairs = 0;
+    while !writer.is_empty() {
+        writer.pop();
+        n_pairs += 1;
+    }
+    n_pairs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::{Channel, Control, ControlCode, Field};
+    use crate::tests::*;
+
+    #[test]
+    fn bandwidth_matches_writer() {
+        test_init_log();
+        let control = Code::Control(ControlCode::new(
+            Field::ONE,
+            Channel::ONE,
+            Control::CarriageReturn,
+        ));
+        let capital_e_acute = Code::from_char('É', Channel::ONE).unwrap();
+        let trademark = Code::from_char('™', Channel::ONE).unwrap();
+        let sequences: [&[Code]; 7] = [
+            &[],
+            &[Code::LatinCapitalA],
+            &[Code::LatinCapitalA, Code::LatinCapitalB],
+            &[
+                Code::LatinCapitalA,
+                Code::LatinCapitalB,
+                Code::LatinCapitalC,
+            ],
+            &[control, control, Code::LatinCapitalA, control],
+            &[Code::LatinCapitalA, capital_e_acute, Code::LatinCapitalB],
+            &[
+                capital_e_acute,
+                trademark,
+                capital_e_acute,
+                Code::LatinCapitalA,
+            ],
+        ];
+        for codes in sequences {
+            let mut writer = Cea608Writer::default();
+            for code in codes {
+                writer.push(*code);
+            }
+            let drained = writer.drain_all();
+            debug!("{codes:?} -> {drained:x?}");
+            assert_eq!(pair_count(codes), drained.len());
+        }
+        assert_eq!(pair_count(sequences[5]), 3);
+    }
+}
@@ -0,0 +1,443 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for reading and writing CEA-608 byte pairs in the `cc_data` layout of CEA-708.
+//!
+//! This is the layout used by GStreamer for closed captions of type `cea708-raw`, e.g. in
+//! `GstVideoCaptionMeta`.  The data is a sequence of 3 byte entries:
+//!
+//! | Byte | Bits | Meaning |
+//! | ---- | ---- | ------- |
+//! | 0    | 7..3 | Marker bits, all set to 1 |
+//! | 0    | 2    | `cc_valid`, whether the entry contains valid data |
+//! | 0    | 1..0 | `cc_type`, 0 for CEA-608 field 1, 1 for CEA-608 field 2, 2 and 3 for CEA-708 |
+//! | 1    |      | The first byte of the CEA-608 byte pair |
+//! | 2    |      | The second byte of the CEA-608 byte pair |
+//!
+//! In ATSC A/53 `cc_data()`, e.g. as carried in SMPTE 334 ancillary data, these entries are
+//! wrapped with a header and a trailing marker byte:
+//!
+//! | Byte | Bits | Meaning |
+//! | ---- | ---- | ------- |
+//! | 0    | 7    | Reserved, set to 1 |
+//! | 0    | 6    | `process_cc_data_flag`, whether the entries should be processed |
+//! | 0    | 5    | `additional_data_flag`, set to 0 |
+//! | 0    | 4..0 | `cc_count`, the number of entries |
+//! | 1    |      | `em_data`, set to `0xff` |
+//! | 2..  |      | `cc_count` entries |
+//! | last |      | Marker bits, set to `0xff` |
+//!
+//! [`split_cc_data`] reads this structure.  With the `atsc` feature, `to_atsc_cc_data()`
+//! produces it.
+
+use crate::tables::{Channel, Code, Field};
+use crate::Id;
+#[cfg(feature = "atsc")]
+use crate::WriterError;
+
+/// Parse CEA-608 byte pairs from `cc_data`.  See the [module documentation](self) for the
+/// layout.
+///
+/// Entries that are not valid or contain CEA-708 data are skipped.  Trailing bytes that do not
+/// form a complete entry are ignored.
+///
+/// The caption [`Channel`] of a byte pair is only signalled by control codes, so the [`Id`]
+/// is derived from the last control code received in the same [`Field`].  Byte pairs before the
+/// first control code of a field are assigned to [`Channel::ONE`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Id, cc_data::from_gst_cc_data};
+/// // RU2 on CC4, 'A' on CC4 and a CEA-708 entry
+/// let data = [0xfd, 0x9d, 0x25, 0xfd, 0xc1, 0x80, 0xfe, 0x01, 0x02];
+/// assert_eq!(
+///     from_gst_cc_data(&data).collect::<Vec<_>>(),
+///     [(Id::CC4, [0x9d, 0x25]), (Id::CC4, [0xc1, 0x80])]
+/// );
+/// ```
+pub fn from_gst_cc_data(data: &[u8]) -> GstCcData<'_> {
+    if data.len() % 3 != 0 {
+        debug!("Ignoring {} trailing bytes of cc_data", data.len() % 3);
+    }
+    GstCcData {
+        entries: data.chunks_exact(3).enumerate(),
+        channels: [Channel::ONE; 2],
+        check_field_interleaving: false,
+        seen: [false; 2],
+        warnings: vec![],
+    }
+}
+
+/// An irregular order of CEA-608 entries in the `cc_data` of a single frame.  See
+/// [`GstCcData::check_field_interleaving`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInterleavingWarning {
+    /// The index of the offending entry
+    pub entry: usize,
+    /// The [`Field`] of the offending entry
+    pub field: Field,
+}
+
+/// Iterator returned by [`from_gst_cc_data`]
+#[derive(Debug)]
+pub struct GstCcData<'a> {
+    entries: std::iter::Enumerate<std::slice::ChunksExact<'a, u8>>,
+    channels: [Channel; 2],
+    check_field_interleaving: bool,
+    seen: [bool; 2],
+    warnings: Vec<FieldInterleavingWarning>,
+}
+
+impl GstCcData<'_> {
+    /// Set whether to check that the `cc_data` contains at most one valid CEA-608 entry per
+    /// [`Field`], with field 1 before field 2, as expected for the data of a single frame.
+    /// Disabled by default.
+    ///
+    /// Each entry that breaks this pattern, e.g. a second field 1 entry, is recorded as a
+    /// [`FieldInterleavingWarning`] and is still produced by the iterator.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{cc_data::{from_gst_cc_data, FieldInterleavingWarning}, tables::Field};
+    /// let data = [0xfc, 0x80, 0x80, 0xfc, 0x80, 0x80];
+    /// let mut cc_data = from_gst_cc_data(&data).check_field_interleaving(true);
+    /// assert_eq!(cc_data.by_ref().count(), 2);
+    /// assert_eq!(
+    ///     cc_data.warnings(),
+    ///     [FieldInterleavingWarning { entry: 1, field: Field::ONE }]
+    /// );
+    /// ```
+    pub fn check_field_interleaving(mut self, check: bool) -> Self {
+        self.check_field_interleaving = check;
+        self
+    }
+
+    /// The [`FieldInterleavingWarning`]s found in the entries iterated over so far
+    pub fn warnings(&self) -> &[FieldInterleavingWarning] {
+        &self.warnings
+    }
+
+    fn check_field(&mut self, entry: usize, field: Field) {
+        let idx = if field == Field::ONE { 0 } else { 1 };
+        if self.seen[idx] || (field == Field::ONE && self.seen[1]) {
+            warn!("Unexpected {field:?} entry {entry} in cc_data");
+            self.warnings
+                .push(FieldInterleavingWarning { entry, field });
+        }
+        self.seen[idx] = true;
+    }
+}
+
+impl Iterator for GstCcData<'_> {
+    type Item = (Id, [u8; 2]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((i, entry)) = self.entries.next() {
+            let cc_valid = entry[0] & 0x04 != 0;
+            let field = match entry[0] & 0x03 {
+                0 => Field::ONE,
+                1 => Field::TWO,
+                _ => continue,
+            };
+            if !cc_valid {
+                continue;
+            }
+            if self.check_field_interleaving {
+                self.check_field(i, field);
+            }
+            let pair = [entry[1], entry[2]];
+            let channel = &mut self.channels[if field == Field::ONE { 0 } else { 1 }];
+            if let Ok([Code::Control(control_code), _]) = Code::from_data(pair) {
+                *channel = control_code.channel();
+            }
+            return Some((Id::from_caption_field_channel(field, *channel), pair));
+        }
+        None
+    }
+}
+
+/// Produce `cc_data` containing the provided CEA-608 byte pairs.  See the
+/// [module documentation](self) for the layout.
+///
+/// Each byte pair is written as a valid entry with the `cc_type` of the [`Field`] of its [`Id`].
+/// The [`Channel`] of the [`Id`] must already be signalled by the byte pairs themselves.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Id, cc_data::to_gst_cc_data};
+/// let entries = [(Id::CC1, [0x94, 0x25]), (Id::CC3, [0x15, 0x25])];
+/// assert_eq!(to_gst_cc_data(&entries), [0xfc, 0x94, 0x25, 0xfd, 0x15, 0x25]);
+/// ```
+pub fn to_gst_cc_data(entries: &[(Id, [u8; 2])]) -> Vec<u8> {
+    entries
+        .iter()
+        .flat_map(|(id, pair)| {
+            let cc_type = match id.field() {
+                Field::ONE => 0x00,
+                Field::TWO => 0x01,
+            };
+            [0xf8 | 0x04 | cc_type, pair[0], pair[1]]
+        })
+        .collect()
+}
+
+/// Split ATSC A/53 `cc_data()` into the CEA-608 byte pairs and the CEA-708 data.  See the
+/// [module documentation](self) for the layout.
+///
+/// Returns the valid CEA-608 byte pairs with their [`Field`], and the complete 3 byte entries
+/// with a CEA-708 `cc_type`, including entries that are not valid, for passing on to a CEA-708
+/// decoder.
+///
+/// Nothing is returned if the `process_cc_data_flag` is not set.  Entries without all marker
+/// bits set are skipped.  If the data is shorter than `cc_count` entries, only the complete
+/// entries are used.  Any data after the entries, including the trailing marker byte, is
+/// ignored.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{cc_data::split_cc_data, tables::Field};
+/// let data = [
+///     0xc3, 0xff,
+///     // RU2 on CC1, a CEA-708 packet start and RU2 on CC3
+///     0xfc, 0x94, 0x25, 0xff, 0x02, 0x21, 0xfd, 0x15, 0x25,
+///     0xff,
+/// ];
+/// let (cea608, cea708) = split_cc_data(&data);
+/// assert_eq!(cea608, [(Field::ONE, [0x94, 0x25]), (Field::TWO, [0x15, 0x25])]);
+/// assert_eq!(cea708, [0xff, 0x02, 0x21]);
+/// ```
+pub fn split_cc_data(data: &[u8]) -> (Vec<(Field, [u8; 2])>, Vec<u8>) {
+    let mut cea608 = vec![];
+    let mut cea708 = vec![];
+    let Some((header, data)) = data.split_first() else {
+        return (cea608, cea708);
+    };
+    if header & 0x40 == 0 {
+        debug!("process_cc_data_flag not set, ignoring cc_data");
+        return (cea608, cea708);
+    }
+    let cc_count = (header & 0x1f) as usize;
+    // skip em_data
+    let entries = data.get(1..).unwrap_or_default();
+    if entries.len() / 3 < cc_count {
+        debug!(
+            "cc_data contains {} of {cc_count} entries",
+            entries.len() / 3
+        );
+    }
+    for entry in entries.chunks_exact(3).take(cc_count) {
+        if entry[0] & 0xf8 != 0xf8 {
+            debug!("Skipping cc_data entry {entry:x?} without marker bits");
+            continue;
+        }
+        let cc_valid = entry[0] & 0x04 != 0;
+        match entry[0] & 0x03 {
+            0 if cc_valid => cea608.push((Field::ONE, [entry[1], entry[2]])),
+            1 if cc_valid => cea608.push((Field::TWO, [entry[1], entry[2]])),
+            0 | 1 => (),
+            _ => cea708.extend_from_slice(entry),
+        }
+    }
+    (cea608, cea708)
+}
+
+/// The maximum number of entries in the ATSC `cc_data()` produced by [`to_atsc_cc_data`]
+#[cfg(feature = "atsc")]
+pub const ATSC_MAX_CC_COUNT: usize = 31;
+
+/// Produce the ATSC A/53 `cc_data()` structure containing the provided CEA-608 byte pairs, e.g.
+/// as the payload for SMPTE 334 ancillary data.  See the [module documentation](self) for the
+/// layout.  Requires the `atsc` feature.
+///
+/// The `process_cc_data_flag` is set and the entries are produced as by [`to_gst_cc_data`].
+///
+/// Returns [`WriterError::WouldOverflow`] with the number of excess bytes if there are more than
+/// [`ATSC_MAX_CC_COUNT`] entries.  Padding the data to the `cc_count` expected for the frame
+/// rate is left to the caller.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Id, cc_data::to_atsc_cc_data};
+/// let entries = [(Id::CC1, [0x94, 0x25]), (Id::CC3, [0x15, 0x25])];
+/// assert_eq!(
+///     to_atsc_cc_data(&entries).unwrap(),
+///     [0xc2, 0xff, 0xfc, 0x94, 0x25, 0xfd, 0x15, 0x25, 0xff]
+/// );
+/// ```
+#[cfg(feature = "atsc")]
+pub fn to_atsc_cc_data(entries: &[(Id, [u8; 2])]) -> Result<Vec<u8>, WriterError> {
+    if entries.len() > ATSC_MAX_CC_COUNT {
+        return Err(WriterError::WouldOverflow(
+            (entries.len() - ATSC_MAX_CC_COUNT) * 3,
+        ));
+    }
+    let mut data = Vec::with_capacity(3 + entries.len() * 3);
+    data.push(0xc0 | entries.len() as u8);
+    data.push(0xff);
+    data.extend(to_gst_cc_data(entries));
+    data.push(0xff);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn gst_cc_data_channels() {
+        test_init_log();
+        let entries: [&[u8]; 6] = [
+            // field 1 text before any control code
+            &[0xfc, 0xc1, 0x80],
+            // RU2 on CC2
+            &[0xfc, 0x1c, 0x25],
+            // field 2 text
+            &[0xfd, 0xc1, 0x80],
+            // invalid entry
+            &[0xf8, 0x94, 0x25],
+            // field 1 text
+            &[0xfc, 0xc2, 0x80],
+            // truncated entry
+            &[0xfc, 0x80],
+        ];
+        let data = entries.concat();
+        assert_eq!(
+            from_gst_cc_data(&data).collect::<Vec<_>>(),
+            [
+                (Id::CC1, [0xc1, 0x80]),
+                (Id::CC2, [0x1c, 0x25]),
+                (Id::CC3, [0xc1, 0x80]),
+                (Id::CC2, [0xc2, 0x80]),
+            ]
+        );
+        assert_eq!(from_gst_cc_data(&[0xfc, 0x80]).next(), None);
+    }
+
+    #[test]
+    fn gst_cc_data_roundtrip() {
+        test_init_log();
+        let entries = [
+            (Id::CC1, [0x94, 0x25]),
+            (Id::CC1, [0xc1, 0x80]),
+            (Id::CC4, [0x9d, 0x29]),
+            (Id::CC4, [0xc2, 0x80]),
+            (Id::CC2, [0x1c, 0x2c]),
+            (Id::CC2, [0x80, 0x80]),
+            (Id::CC3, [0x15, 0x2c]),
+        ];
+        let data = to_gst_cc_data(&entries);
+        assert_eq!(data.len(), entries.len() * 3);
+        assert_eq!(data[..3], [0xfc, 0x94, 0x25]);
+        assert_eq!(data[6..9], [0xfd, 0x9d, 0x29]);
+        assert_eq!(from_gst_cc_data(&data).collect::<Vec<_>>(), entries);
+    }
+
+    #[test]
+    fn gst_cc_data_field_interleaving() {
+        test_init_log();
+        let entries: [&[u8]; 5] = [
+            &[0xfc, 0x80, 0x80],
+            &[0xfd, 0x80, 0x80],
+            // a CEA-708 entry and an invalid entry are not checked
+            &[0xfe, 0x01, 0x02],
+            &[0xf8, 0x80, 0x80],
+            // field 1 after field 2
+            &[0xfc, 0x80, 0x80],
+        ];
+        let data = entries.concat();
+        let mut cc_data = from_gst_cc_data(&data);
+        assert_eq!(cc_data.by_ref().count(), 3);
+        assert_eq!(cc_data.warnings(), []);
+        let mut cc_data = from_gst_cc_data(&data).check_field_interleaving(true);
+        assert_eq!(cc_data.by_ref().count(), 3);
+        assert_eq!(
+            cc_data.warnings(),
+            [FieldInterleavingWarning {
+                entry: 4,
+                field: Field::ONE
+            }]
+        );
+        let data = [0xfd, 0x80, 0x80, 0xfd, 0x80, 0x80];
+        let mut cc_data = from_gst_cc_data(&data).check_field_interleaving(true);
+        assert_eq!(cc_data.by_ref().count(), 2);
+        assert_eq!(
+            cc_data.warnings(),
+            [FieldInterleavingWarning {
+                entry: 1,
+                field: Field::TWO
+            }]
+        );
+    }
+
+    #[test]
+    fn split_cc_data_mixed() {
+        test_init_log();
+        let entries: [&[u8]; 8] = [
+            // process_cc_data_flag, cc_count 5 and em_data
+            &[0xc5, 0xff],
+            &[0xfc, 0x94, 0x2c],
+            // invalid CEA-708 entries are kept
+            &[0xfa, 0x00, 0x00],
+            // missing marker bits
+            &[0x04, 0x94, 0x2c],
+            // invalid CEA-608 entry
+            &[0xf9, 0x80, 0x80],
+            &[0xfe, 0x01, 0x02],
+            // after cc_count
+            &[0xfd, 0x15, 0x2c],
+            &[0xff],
+        ];
+        let data = entries.concat();
+        assert_eq!(
+            split_cc_data(&data),
+            (
+                vec![(Field::ONE, [0x94, 0x2c])],
+                vec![0xfa, 0x00, 0x00, 0xfe, 0x01, 0x02]
+            )
+        );
+        // process_cc_data_flag not set
+        let mut unprocessed = data.clone();
+        unprocessed[0] &= !0x40;
+        assert_eq!(split_cc_data(&unprocessed), (vec![], vec![]));
+        // truncated
+        assert_eq!(
+            split_cc_data(&data[..6]),
+            (vec![(Field::ONE, [0x94, 0x2c])], vec![])
+        );
+        assert_eq!(split_cc_data(&[]), (vec![], vec![]));
+        assert_eq!(split_cc_data(&[0xc1]), (vec![], vec![]));
+    }
+
+    #[cfg(feature = "atsc")]
+    #[test]
+    fn atsc_cc_data() {
+        test_init_log();
+        assert_eq!(to_atsc_cc_data(&[]).unwrap(), [0xc0, 0xff, 0xff]);
+        let entries = [(Id::CC1, [0x94, 0x25]), (Id::CC4, [0x9d, 0x29])];
+        let data = to_atsc_cc_data(&entries).unwrap();
+        assert_eq!(data[..2], [0xc2, 0xff]);
+        assert_eq!(data[data.len() - 1], 0xff);
+        assert_eq!(
+            from_gst_cc_data(&data[2..data.len() - 1]).collect::<Vec<_>>(),
+            entries
+        );
+        assert_eq!(
+            split_cc_data(&data),
+            (
+                vec![(Field::ONE, [0x94, 0x25]), (Field::TWO, [0x9d, 0x29])],
+                vec![]
+            )
+        );
+        let entries = [(Id::CC1, [0x80, 0x80]); ATSC_MAX_CC_COUNT];
+        assert_eq!(to_atsc_cc_data(&entries).unwrap()[0], 0xdf);
+        let entries = [(Id::CC1, [0x80, 0x80]); ATSC_MAX_CC_COUNT + 2];
+        assert_eq!(
+            to_atsc_cc_data(&entries),
+            Err(WriterError::WouldOverflow(6))
+        );
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for combining consecutive [`Cea608`] events, e.g. [`Cea608::Text`] events into strings
+
+use std::collections::VecDeque;
+
+use crate::screen::N_COLUMNS;
+use crate::tables::{Channel, PreambleAddressCode};
+use crate::{Cea608, Text};
+
+/// An item produced by [`coalesce_text`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Coalesced {
+    /// A run of consecutive text on a single channel
+    TextRun {
+        /// The channel the text was received on
+        channel: Channel,
+        /// The text
+        text: String,
+    },
+    /// Any other event
+    Event(Cea608),
+}
+
+/// Combine consecutive [`Cea608::Text`] events on the same channel into a single
+/// [`Coalesced::TextRun`].
+///
+/// A text run is finished when a non-text event is received, or when text is received on a
+/// different channel.  Characters that need a backspace replace the previous character in the
+/// current run.  If there is no previous character in the current run, a [`Cea608::Backspace`]
+/// event is produced before the new run.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Cea608, Text, tables::Channel};
+/// # use cea608_types::coalesce::{coalesce_text, Coalesced};
+/// let text = |c| {
+///     Cea608::Text(Text {
+///         needs_backspace: false,
+///         char1: Some(c),
+///         char2: None,
+///         channel: Channel::ONE,
+///     })
+/// };
+/// let events = [text('H'), text('i'), Cea608::CarriageReturn(Channel::ONE)];
+/// let coalesced = coalesce_text(events).collect::<Vec<_>>();
+/// assert_eq!(
+///     coalesced,
+///     [
+///         Coalesced::TextRun { channel: Channel::ONE, text: String::from("Hi") },
+///         Coalesced::Event(Cea608::CarriageReturn(Channel::ONE)),
+///     ]
+/// );
+/// ```
+pub fn coalesce_text<I: IntoIterator<Item = Cea608>>(iter: I) -> CoalesceText<I::IntoIter> {
+    CoalesceText {
+        iter: iter.into_iter(),
+        run: None,
+        queued: VecDeque::new(),
+    }
+}
+
+/// Iterator returned by [`coalesce_text`]
+#[derive(Debug)]
+pub struct CoalesceText<I> {
+    iter: I,
+    run: Option<(Channel, String)>,
+    queued: VecDeque<Coalesced>,
+}
+
+impl<I> CoalesceText<I> {
+    fn flush_run(&mut self) {
+        if let Some((channel, text)) = self.run.take() {
+            self.queued.push_back(Coalesced::TextRun { channel, text });
+        }
+    }
+
+    fn push_text(&mut self, text: Text) {
+        if self
+            .run
+            .as_ref()
+            .is_some_and(|(channel, _)| *channel != text.channel)
+        {
+            self.flush_run();
+        }
+        let (_, run) = self
+            .run
+            .get_or_insert_with(|| (text.channel, String::new()));
+        if text.needs_backspace && run.pop().is_none() {
+            self.queued
+                .push_back(Coalesced::Event(Cea608::Backspace(text.channel)));
+        }
+        run.extend(text.char1);
+        run.extend(text.char2);
+    }
+}
+
+impl<I: Iterator<Item = Cea608>> Iterator for CoalesceText<I> {
+    type Item = Coalesced;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.queued.is_empty() {
+            match self.iter.next() {
+                Some(Cea608::Text(text)) => self.push_text(text),
+                Some(event) => {
+                    self.flush_run();
+                    self.queued.push_back(Coalesced::Event(event));
+                }
+                None => {
+                    self.flush_run();
+                    break;
+                }
+            }
+        }
+        self.queued.pop_front()
+    }
+}
+
+/// An item produced by [`coalesce_positioning`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Positioned {
+    /// A preamble, including any directly following tab offset
+    Preamble {
+        /// The channel the preamble was received on
+        channel: Channel,
+        /// The preamble
+        preamble: PreambleAddressCode,
+        /// The absolute column of the cursor after the preamble and tab offset
+        column: u8,
+    },
+    /// Any other event
+    Event(Cea608),
+}
+
+/// Combine a [`Cea608::TabOffset`] that directly follows a [`Cea608::Preamble`] on the same
+/// channel into a single [`Positioned::Preamble`] with the absolute column.
+///
+/// A preamble can only indent in multiples of 4 columns and a tab offset is needed to reach the
+/// columns in between.  Every preamble produces a [`Positioned::Preamble`], with or without a tab
+/// offset.  The column never exceeds the last column.  Iterate the events directly to keep the
+/// separate preamble and tab offset events.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Cea608, tables::{Channel, PreambleAddressCode, PreambleType}};
+/// # use cea608_types::coalesce::{coalesce_positioning, Positioned};
+/// let preamble = PreambleAddressCode::new(14, false, PreambleType::Indent4);
+/// let events = [
+///     Cea608::Preamble(Channel::ONE, preamble),
+///     Cea608::TabOffset(Channel::ONE, 2),
+/// ];
+/// assert_eq!(
+///     coalesce_positioning(events).collect::<Vec<_>>(),
+///     [Positioned::Preamble { channel: Channel::ONE, preamble, column: 6 }]
+/// );
+/// ```
+pub fn coalesce_positioning<I: IntoIterator<Item = Cea608>>(
+    iter: I,
+) -> CoalescePositioning<I::IntoIter> {
+    CoalescePositioning {
+        iter: iter.into_iter(),
+        peeked: None,
+    }
+}
+
+/// Iterator returned by [`coalesce_positioning`]
+#[derive(Debug)]
+pub struct CoalescePositioning<I> {
+    iter: I,
+    peeked: Option<Cea608>,
+}
+
+impl<I: Iterator<Item = Cea608>> Iterator for CoalescePositioning<I> {
+    type Item = Positioned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.peeked.take().or_else(|| self.iter.next())?;
+        let Cea608::Preamble(channel, preamble) = event else {
+            return Some(Positioned::Event(event));
+        };
+        let mut column = preamble.column();
+        match self.iter.next() {
+            Some(Cea608::TabOffset(tab_channel, offset)) if tab_channel == channel => {
+                column = (column + offset).min(N_COLUMNS - 1);
+            }
+            next => self.peeked = next,
+        }
+        Some(Positioned::Preamble {
+            channel,
+            preamble,
+            column,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    fn text(c: char, needs_backspace: bool, channel: Channel) -> Cea608 {
+        Cea608::Text(Text {
+            needs_backspace,
+            char1: Some(c),
+            char2: None,
+            channel,
+        })
+    }
+
+    #[test]
+    fn coalesce_backspace() {
+        test_init_log();
+        let events = [
+            text('a', false, Channel::ONE),
+            text(' ', false, Channel::ONE),
+            text('é', true, Channel::ONE),
+        ];
+        assert_eq!(
+            coalesce_text(events).collect::<Vec<_>>(),
+            [Coalesced::TextRun {
+                channel: Channel::ONE,
+                text: String::from("aé")
+            }]
+        );
+    }
+
+    #[test]
+    fn coalesce_backspace_at_run_start() {
+        test_init_log();
+        let events = [
+            Cea608::MidRowChange(Channel::ONE, crate::tables::MidRow::new_italics(false)),
+            text('é', true, Channel::ONE),
+        ];
+        assert_eq!(
+            coalesce_text(events).collect::<Vec<_>>(),
+            [
+                Coalesced::Event(events[0]),
+                Coalesced::Event(Cea608::Backspace(Channel::ONE)),
+                Coalesced::TextRun {
+                    channel: Channel::ONE,
+                    text: String::from("é")
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn coalesce_preamble_tab_offset() {
+        test_init_log();
+        let indent8 = PreambleAddressCode::new(3, false, crate::tables::PreambleType::Indent8);
+        let indent28 = PreambleAddressCode::new(4, true, crate::tables::PreambleType::Indent28);
+        let events = [
+            Cea608::Preamble(Channel::ONE, indent8),
+            Cea608::TabOffset(Channel::ONE, 2),
+            Cea608::TabOffset(Channel::ONE, 1),
+            Cea608::Preamble(Channel::ONE, indent8),
+            Cea608::TabOffset(Channel::TWO, 3),
+            Cea608::Preamble(Channel::ONE, indent28),
+            Cea608::TabOffset(Channel::ONE, 3),
+            Cea608::Preamble(Channel::ONE, indent8),
+        ];
+        assert_eq!(
+            coalesce_positioning(events).collect::<Vec<_>>(),
+            [
+                Positioned::Preamble {
+                    channel: Channel::ONE,
+                    preamble: indent8,
+                    column: 10
+                },
+                Positioned::Event(Cea608::TabOffset(Channel::ONE, 1)),
+                Positioned::Preamble {
+                    channel: Channel::ONE,
+                    preamble: indent8,
+                    column: 8
+                },
+                Positioned::Event(Cea608::TabOffset(Channel::TWO, 3)),
+                Positioned::Preamble {
+                    channel: Channel::ONE,
+                    preamble: indent28,
+                    column: 31
+                },
+                Positioned::Preamble {
+                    channel: Channel::ONE,
+                    preamble: indent8,
+                    column: 8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn coalesce_channel_change() {
+        test_init_log();
+        let events = [
+            Cea608::Text(Text {
+                needs_backspace: false,
+                char1: Some('a'),
+                char2: Some('b'),
+                channel: Channel::ONE,
+            }),
+            text('c', false, Channel::TWO),
+            text('d', false, Channel::TWO),
+            Cea608::EndOfCaption(Channel::TWO),
+        ];
+        assert_eq!(
+            coalesce_text(events).collect::<Vec<_>>(),
+            [
+                Coalesced::TextRun {
+                    channel: Channel::ONE,
+                    text: String::from("ab")
+                },
+                Coalesced::TextRun {
+                    channel: Channel::TWO,
+                    text: String::from("cd")
+                },
+                Coalesced::Event(Cea608::EndOfCaption(Channel::TWO)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for checking a sequence of CEA-608 byte pairs for conformance
+
+use crate::tables::{Code, CodeError};
+
+/// An issue found when checking a sequence of byte pairs for conformance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConformanceIssue {
+    /// One of the bytes in the pair does not have odd parity
+    InvalidParity {
+        /// The index of the offending byte pair
+        position: usize,
+    },
+    /// A control code was not immediately followed by an identical copy of itself
+    UndoubledControl {
+        /// The index of the offending byte pair
+        position: usize,
+        /// The byte pair containing the control code
+        data: [u8; 2],
+    },
+}
+
+impl ConformanceIssue {
+    /// The index of the byte pair this issue refers to
+    pub fn position(&self) -> usize {
+        match self {
+            Self::InvalidParity { position } => *position,
+            Self::UndoubledControl { position, .. } => *position,
+        }
+    }
+}
+
+/// Check a sequence of byte pairs (one pair per frame) for conformance issues.
+///
+/// Every control code must be transmitted twice in consecutive byte pairs.  A control code
+/// transmitted on its own is reported as [`ConformanceIssue::UndoubledControl`].  Byte pairs with
+/// invalid parity are reported as [`ConformanceIssue::InvalidParity`].
+///
+/// The returned issues are ordered by position.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::conformance::{check, ConformanceIssue};
+/// let issues = check(&[[0x94, 0x2c], [0x94, 0x2c], [0x94, 0x2f], [0x80, 0x80]]);
+/// assert_eq!(issues, [ConformanceIssue::UndoubledControl { position: 2, data: [0x94, 0x2f] }]);
+/// ```
+pub fn check(pairs: &[[u8; 2]]) -> Vec<ConformanceIssue> {
+    let mut issues = vec![];
+    let mut i = 0;
+
+    while i < pairs.len() {
+        let data = pairs[i];
+        match Code::from_data(data) {
+            Err(CodeError::InvalidParity) => {
+                issues.push(ConformanceIssue::InvalidParity { position: i });
+            }
+            Err(_) => (),
+            Ok([Code::Control(_), _]) => {
+                if pairs.get(i + 1) == Some(&data) {
+                    i += 1;
+                } else {
+                    issues.push(ConformanceIssue::UndoubledControl { position: i, data });
+                }
+            }
+            Ok(_) => (),
+        }
+        i += 1;
+    }
+
+    issues
+}
+
+/// Double every control code that [`check`] reports as a
+/// [`ConformanceIssue::UndoubledControl`].
+///
+/// A copy of each undoubled control code is inserted directly after it.  All other byte pairs,
+/// including those with invalid parity, are kept unchanged.  The output is therefore longer than
+/// the input by the number of inserted copies and any following data is delayed accordingly.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::conformance::ensure_doubling;
+/// assert_eq!(
+///     ensure_doubling(&[[0x94, 0x2c], [0xc1, 0x80]]),
+///     [[0x94, 0x2c], [0x94, 0x2c], [0xc1, 0x80]]
+/// );
+/// ```
+pub fn ensure_doubling(pairs: &[[u8; 2]]) -> Vec<[u8; 2]> {
+    let mut undoubled = check(pairs)
+        .into_iter()
+        .filter_map(|issue| match issue {
+            ConformanceIssue::UndoubledControl { position, .. } => Some(position),
+            _ => None,
+        })
+        .peekable();
+    let mut ret = Vec::with_capacity(pairs.len());
+    for (i, data) in pairs.iter().enumerate() {
+        ret.push(*data);
+        if undoubled.next_if_eq(&i).is_some() {
+            ret.push(*data);
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn conformance_doubled_control() {
+        test_init_log();
+        assert_eq!(check(&[[0x94, 0x2c], [0x94, 0x2c], [0xc1, 0x80]]), []);
+    }
+
+    #[test]
+    fn conformance_undoubled_control() {
+        test_init_log();
+        assert_eq!(
+            check(&[[0x94, 0x2c], [0x80, 0x80], [0x94, 0x2c]]),
+            [
+                ConformanceIssue::UndoubledControl {
+                    position: 0,
+                    data: [0x94, 0x2c]
+                },
+                ConformanceIssue::UndoubledControl {
+                    position: 2,
+                    data: [0x94, 0x2c]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn conformance_tripled_control() {
+        test_init_log();
+        // the third copy is a new command that has not been doubled
+        assert_eq!(
+            check(&[[0x94, 0x2c], [0x94, 0x2c], [0x94, 0x2c]]),
+            [ConformanceIssue::UndoubledControl {
+                position: 2,
+                data: [0x94, 0x2c]
+            }]
+        );
+    }
+
+    #[test]
+    fn conformance_invalid_parity() {
+        test_init_log();
+        let issues = check(&[[0x80, 0x80], [0x41, 0x80]]);
+        assert_eq!(issues, [ConformanceIssue::InvalidParity { position: 1 }]);
+        assert_eq!(issues[0].position(), 1);
+    }
+
+    #[test]
+    fn conformance_ensure_doubling() {
+        test_init_log();
+        let pairs = [
+            [0x94, 0x25],
+            [0xc1, 0x80],
+            [0x94, 0x2c],
+            [0x94, 0x2c],
+            [0x94, 0x2c],
+            [0x41, 0x80],
+            [0x94, 0xad],
+        ];
+        let doubled = ensure_doubling(&pairs);
+        assert_eq!(
+            doubled,
+            [
+                [0x94, 0x25],
+                [0x94, 0x25],
+                [0xc1, 0x80],
+                [0x94, 0x2c],
+                [0x94, 0x2c],
+                [0x94, 0x2c],
+                [0x94, 0x2c],
+                [0x41, 0x80],
+                [0x94, 0xad],
+                [0x94, 0xad],
+            ]
+        );
+        assert!(check(&doubled)
+            .iter()
+            .all(|issue| matches!(issue, ConformanceIssue::InvalidParity { .. })));
+        assert_eq!(ensure_doubling(&doubled), doubled);
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for reading byte pairs from comma or tab separated captures
+//!
+//! Each line consists of the frame number followed by the two bytes of the pair, e.g.
+//! `120,0x94,0x2c` or `120\t148\t44`.  Every value can be written in decimal or in hexadecimal
+//! with a `0x` prefix.
+
+use std::io::BufRead;
+
+/// Read the frame numbers and byte pairs of a comma or tab separated capture.
+///
+/// Empty lines, comment lines starting with `#` and any other lines that cannot be parsed, e.g. a
+/// header, are skipped.  Reading stops at the first I/O error.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::csv::parse_csv;
+/// let capture = "frame,byte0,byte1\n0,0x94,0x2c\n# comment\n1,193,128\n";
+/// let pairs = parse_csv(capture.as_bytes()).collect::<Vec<_>>();
+/// assert_eq!(pairs, [(0, [0x94, 0x2c]), (1, [0xc1, 0x80])]);
+/// ```
+pub fn parse_csv<R: BufRead>(reader: R) -> ParseCsv<R> {
+    ParseCsv {
+        lines: reader.lines(),
+    }
+}
+
+/// Iterator returned by [`parse_csv`]
+#[derive(Debug)]
+pub struct ParseCsv<R> {
+    lines: std::io::Lines<R>,
+}
+
+impl<R: BufRead> Iterator for ParseCsv<R> {
+    type Item = (u64, [u8; 2]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => {
+                    warn!("Failed to read line: {err}");
+                    return None;
+                }
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(parsed) = parse_line(line) {
+                return Some(parsed);
+            }
+            debug!("Skipping line {line:?}");
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Option<(u64, [u8; 2])> {
+    let mut values = line
+        .split([',', '\t'])
+        .map(|value| parse_value(value.trim()));
+    let frame = values.next()??;
+    let byte0 = u8::try_from(values.next()??).ok()?;
+    let byte1 = u8::try_from(values.next()??).ok()?;
+    if values.next().is_some() {
+        return None;
+    }
+    Some((frame, [byte0, byte1]))
+}
+
+fn parse_value(value: &str) -> Option<u64> {
+    if let Some(hex) = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        value.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn csv_rows() {
+        test_init_log();
+        let capture = "\
+# captured from CC1
+frame\tbyte0\tbyte1
+
+10\t0x94\t0x20
+11 , 0X94 , 0xAE
+12,193,128
+13,0x94,0x100
+14,0x94
+15,0x80,0x80,0x80
+";
+        assert_eq!(
+            parse_csv(capture.as_bytes()).collect::<Vec<_>>(),
+            [(10, [0x94, 0x20]), (11, [0x94, 0xae]), (12, [0xc1, 0x80])]
+        );
+    }
+}
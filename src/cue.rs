@@ -0,0 +1,264 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for turning decoded [`Cea608`] events into timed cues
+
+use crate::screen::Cea608Screen;
+use crate::tables::Channel;
+use crate::{Cea608, Mode};
+
+/// A Pop-On caption produced by [`pop_on_cues`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PopOnCue {
+    /// The index of the [`Cea608::EndOfCaption`] event that displayed the caption
+    pub start_index: usize,
+    /// The index of the event that removed the caption from the display, or None if the caption
+    /// was still displayed after the last event
+    pub end_index: Option<usize>,
+    /// The displayed text.  Each row is a separate line without leading or trailing whitespace.
+    pub text: String,
+}
+
+/// Produce a [`PopOnCue`] for each Pop-On caption displayed on `channel`.
+///
+/// A caption is displayed by a [`Cea608::EndOfCaption`] in Pop-On mode that swaps in the text
+/// loaded into the non-displayed memory.  The caption ends with the next
+/// [`Cea608::EndOfCaption`] or with any other event that changes the displayed text, e.g. a
+/// [`Cea608::EraseDisplay`].  The indices refer to the position of the events in `iter`, which
+/// can be used to look up the timestamp of each event.  Events for other channels are ignored.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Cea608, Mode, Text, tables::Channel};
+/// # use cea608_types::cue::{pop_on_cues, PopOnCue};
+/// let events = [
+///     Cea608::NewMode(Channel::ONE, Mode::PopOn),
+///     Cea608::Text(Text {
+///         needs_backspace: false,
+///         char1: Some('H'),
+///         char2: Some('i'),
+///         channel: Channel::ONE,
+///     }),
+///     Cea608::EndOfCaption(Channel::ONE),
+///     Cea608::EraseDisplay(Channel::ONE),
+/// ];
+/// let cues = pop_on_cues(events, Channel::ONE).collect::<Vec<_>>();
+/// assert_eq!(
+///     cues,
+///     [PopOnCue { start_index: 2, end_index: Some(3), text: String::from("Hi") }]
+/// );
+/// ```
+pub fn pop_on_cues<I: IntoIterator<Item = Cea608>>(
+    iter: I,
+    channel: Channel,
+) -> PopOnCues<I::IntoIter> {
+    PopOnCues {
+        iter: iter.into_iter().enumerate(),
+        screen: Cea608Screen::new(channel),
+        current: None,
+    }
+}
+
+/// Iterator returned by [`pop_on_cues`]
+#[derive(Debug)]
+pub struct PopOnCues<I> {
+    iter: std::iter::Enumerate<I>,
+    screen: Cea608Screen,
+    current: Option<(usize, String)>,
+}
+
+fn displayed_text(screen: &Cea608Screen) -> String {
+    screen
+        .displayed_text()
+        .iter()
+        .map(|(_, text)| text.trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl<I: Iterator<Item = Cea608>> Iterator for PopOnCues<I> {
+    type Item = PopOnCue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (idx, event) in self.iter.by_ref() {
+            if event.channel() != self.screen.channel() {
+                continue;
+            }
+            self.screen.apply(&event);
+            let is_eoc = matches!(event, Cea608::EndOfCaption(_));
+            let text = displayed_text(&self.screen);
+            let ended = if is_eoc
+                || self
+                    .current
+                    .as_ref()
+                    .is_some_and(|(_, current)| *current != text)
+            {
+                self.current.take()
+            } else {
+                None
+            };
+            if is_eoc && self.screen.mode() == Some(Mode::PopOn) && !text.is_empty() {
+                self.current = Some((idx, text));
+            }
+            if let Some((start_index, text)) = ended {
+                return Some(PopOnCue {
+                    start_index,
+                    end_index: Some(idx),
+                    text,
+                });
+            }
+        }
+        let (start_index, text) = self.current.take()?;
+        Some(PopOnCue {
+            start_index,
+            end_index: None,
+            text,
+        })
+    }
+}
+
+/// Compute how long each Pop-On caption on `channel` was displayed from events with per-pair
+/// timing, e.g. the frame number or timestamp of the byte pair that produced each event.
+///
+/// Returns the displayed text, the time of the event that displayed the caption and the time of
+/// the event that removed it, or None if the caption was still displayed after the last event.
+/// See [`pop_on_cues`] for how captions are detected.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Cea608, Mode, Text, tables::Channel};
+/// # use cea608_types::cue::display_durations;
+/// let events = [
+///     (10, Cea608::NewMode(Channel::ONE, Mode::PopOn)),
+///     (11, Cea608::Text(Text {
+///         needs_backspace: false,
+///         char1: Some('H'),
+///         char2: Some('i'),
+///         channel: Channel::ONE,
+///     })),
+///     (12, Cea608::EndOfCaption(Channel::ONE)),
+///     (72, Cea608::EraseDisplay(Channel::ONE)),
+/// ];
+/// assert_eq!(
+///     display_durations(events, Channel::ONE),
+///     [(String::from("Hi"), 12, Some(72))]
+/// );
+/// ```
+pub fn display_durations<T: Copy, I: IntoIterator<Item = (T, Cea608)>>(
+    iter: I,
+    channel: Channel,
+) -> Vec<(String, T, Option<T>)> {
+    let (times, events): (Vec<_>, Vec<_>) = iter.into_iter().unzip();
+    pop_on_cues(events, channel)
+        .map(|cue| {
+            (
+                cue.text,
+                times[cue.start_index],
+                cue.end_index.map(|idx| times[idx]),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::{PreambleAddressCode, PreambleType};
+    use crate::tests::*;
+    use crate::Text;
+
+    fn caption(row: u8, s: &str) -> Vec<Cea608> {
+        let mut events = vec![
+            Cea608::NewMode(Channel::ONE, Mode::PopOn),
+            Cea608::EraseNonDisplay(Channel::ONE),
+            Cea608::Preamble(
+                Channel::ONE,
+                PreambleAddressCode::new(row, false, PreambleType::Indent4),
+            ),
+        ];
+        events.extend(s.chars().map(|c| {
+            Cea608::Text(Text {
+                needs_backspace: false,
+                char1: Some(c),
+                char2: None,
+                channel: Channel::ONE,
+            })
+        }));
+        events.push(Cea608::EndOfCaption(Channel::ONE));
+        events
+    }
+
+    #[test]
+    fn cue_two_captions() {
+        test_init_log();
+        let mut events = caption(13, "one");
+        let first_eoc = events.len() - 1;
+        events.push(Cea608::EraseNonDisplay(Channel::TWO));
+        events.extend(caption(14, "two"));
+        let second_eoc = events.len() - 1;
+        events.push(Cea608::EraseDisplay(Channel::ONE));
+        let erase = events.len() - 1;
+        assert_eq!(
+            pop_on_cues(events.clone(), Channel::ONE).collect::<Vec<_>>(),
+            [
+                PopOnCue {
+                    start_index: first_eoc,
+                    end_index: Some(second_eoc),
+                    text: String::from("one"),
+                },
+                PopOnCue {
+                    start_index: second_eoc,
+                    end_index: Some(erase),
+                    text: String::from("two"),
+                },
+            ]
+        );
+        assert_eq!(pop_on_cues(events, Channel::TWO).count(), 0);
+    }
+
+    #[test]
+    fn cue_unterminated() {
+        test_init_log();
+        assert_eq!(
+            pop_on_cues(caption(14, "one"), Channel::ONE).collect::<Vec<_>>(),
+            [PopOnCue {
+                start_index: 6,
+                end_index: None,
+                text: String::from("one"),
+            }]
+        );
+    }
+
+    #[test]
+    fn cue_display_durations() {
+        test_init_log();
+        let mut events = caption(14, "shown")
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| (i as u64 * 33, event))
+            .collect::<Vec<_>>();
+        let (shown, _) = *events.last().unwrap();
+        events.push((
+            1000,
+            Cea608::Text(Text {
+                needs_backspace: false,
+                char1: Some('x'),
+                char2: None,
+                channel: Channel::ONE,
+            }),
+        ));
+        events.push((2000, Cea608::EraseDisplay(Channel::ONE)));
+        assert_eq!(
+            display_durations(events.clone(), Channel::ONE),
+            [(String::from("shown"), shown, Some(2000))]
+        );
+        events.pop();
+        assert_eq!(
+            display_durations(events, Channel::ONE),
+            [(String::from("shown"), shown, None)]
+        );
+    }
+}
@@ -14,14 +14,85 @@
 //! The reference for this implementation is the [ANSI/CTA-608-E S-2019](https://shop.cta.tech/products/line-21-data-services) specification.
 
 use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 
 use tables::{Channel, Code, Field, MidRow, PreambleAddressCode};
 
 #[macro_use]
 extern crate log;
 
+pub mod author;
+pub mod bandwidth;
+pub mod cc_data;
+pub mod coalesce;
+pub mod conformance;
+pub mod csv;
+pub mod cue;
+pub mod line21;
+pub mod readability;
+pub mod screen;
+pub mod stats;
 pub mod tables;
 
+/// Build the byte pairs for a sequence of control codes and text on a single [`Field`] and
+/// [`Channel`], e.g. for writing decoder tests.  Requires the `test-util` feature.
+///
+/// Each item is either a [`Control`](tables::Control), a `char` or a `&str`.  The items are
+/// pushed into a [`Cea608Writer`] in order and all of the produced byte pairs are returned.  As
+/// with [`Cea608Writer::push`], control codes are not doubled and need to be listed twice if
+/// required.
+///
+/// # Panics
+///
+/// Panics if a character cannot be represented.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{codes, tables::{Channel, Control, Field}};
+/// let data = codes![Field::ONE, Channel::ONE; Control::RollUp2, Control::RollUp2, "Hi", '!'];
+/// assert_eq!(data, [[0x94, 0x25], [0x94, 0x25], [0xc8, 0xe9], [0xa1, 0x80]]);
+/// ```
+#[cfg(any(test, feature = "test-util"))]
+#[macro_export]
+macro_rules! codes {
+    ($field:expr, $channel:expr; $($item:expr),* $(,)?) => {{
+        let mut writer = $crate::Cea608Writer::default();
+        $($crate::CodesItem::push_into($item, &mut writer, $field, $channel);)*
+        writer.drain_all()
+    }};
+}
+
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+pub trait CodesItem {
+    fn push_into(self, writer: &mut Cea608Writer, field: Field, channel: Channel);
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl CodesItem for tables::Control {
+    fn push_into(self, writer: &mut Cea608Writer, field: Field, channel: Channel) {
+        writer.push(Code::Control(tables::ControlCode::new(
+            field, channel, self,
+        )));
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl CodesItem for char {
+    fn push_into(self, writer: &mut Cea608Writer, _field: Field, channel: Channel) {
+        writer.push_char(self, channel).unwrap();
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl CodesItem for &str {
+    fn push_into(self, writer: &mut Cea608Writer, field: Field, channel: Channel) {
+        for c in self.chars() {
+            c.push_into(writer, field, channel);
+        }
+    }
+}
+
 /// Various possible errors when parsing data
 #[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
 pub enum ParserError {
@@ -36,6 +107,9 @@ pub enum ParserError {
         /// The actual size
         actual: usize,
     },
+    /// The value does not correspond to a valid [`Id`]
+    #[error("Invalid caption identifier value {0}")]
+    InvalidId(i8),
 }
 
 /// An error enum returned when writing data fails
@@ -47,6 +121,9 @@ pub enum WriterError {
     /// It is not possible to write to this resource
     #[error("Read only resource")]
     ReadOnly,
+    /// The character cannot be represented in CEA-608
+    #[error("The character {0:?} cannot be represented")]
+    UnrepresentableChar(char),
 }
 
 impl From<tables::CodeError> for ParserError {
@@ -85,6 +162,18 @@ impl Mode {
         matches!(self, Self::RollUp2 | Self::RollUp3 | Self::RollUp4)
     }
 
+    /// Whether text in this mode is placed at absolute positions set by preambles and stays in
+    /// place, i.e. Pop-On and Paint-On
+    pub fn is_absolute_positioning(&self) -> bool {
+        matches!(self, Self::PopOn | Self::PaintOn)
+    }
+
+    /// Whether the rows of text in this mode scroll up on a carriage return, i.e. the roll-up modes.
+    /// See [`is_rollup`](Self::is_rollup).
+    pub fn scrolls(&self) -> bool {
+        self.is_rollup()
+    }
+
     /// How many rows of roll up captions this mode supports.  Other modes will return [`None`].
     pub fn rollup_rows(&self) -> Option<u8> {
         match self {
@@ -94,6 +183,55 @@ impl Mode {
             _ => None,
         }
     }
+
+    /// The [`Control`](tables::Control) code that selects this mode.  The inverse of
+    /// [`Control::to_mode`](tables::Control::to_mode).
+    ///
+    /// Text mode is not a caption mode and is selected with
+    /// [`ResumeTextDisplay`](tables::Control::ResumeTextDisplay).
+    pub fn to_control(&self) -> tables::Control {
+        match self {
+            Self::PopOn => tables::Control::ResumeCaptionLoading,
+            Self::PaintOn => tables::Control::ResumeDirectionCaptioning,
+            Self::RollUp2 => tables::Control::RollUp2,
+            Self::RollUp3 => tables::Control::RollUp3,
+            Self::RollUp4 => tables::Control::RollUp4,
+        }
+    }
+
+    /// The effect on the display of switching from this mode to another mode.
+    ///
+    /// - Switching between different roll-up modes keeps the displayed rows and only changes the
+    ///   size of the roll-up window.
+    /// - Entering a roll-up mode from Pop-On or Paint-On, or leaving a roll-up mode, erases the
+    ///   displayed and non-displayed memory.
+    /// - Switching between Pop-On and Paint-On, or to the same mode, keeps the displayed rows.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Mode, ModeTransition};
+    /// assert_eq!(Mode::RollUp2.transition(Mode::RollUp4), ModeTransition::ResizeWindow);
+    /// assert_eq!(Mode::RollUp2.transition(Mode::PopOn), ModeTransition::ClearScreen);
+    /// assert_eq!(Mode::PopOn.transition(Mode::PaintOn), ModeTransition::PreserveRows);
+    /// ```
+    pub fn transition(&self, to: Mode) -> ModeTransition {
+        match (self.rollup_rows(), to.rollup_rows()) {
+            (Some(from), Some(to)) if from != to => ModeTransition::ResizeWindow,
+            (Some(_), Some(_)) | (None, None) => ModeTransition::PreserveRows,
+            _ => ModeTransition::ClearScreen,
+        }
+    }
+}
+
+/// The effect on the display of a [`Mode`] change.  See [`Mode::transition`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModeTransition {
+    /// The displayed rows are kept
+    PreserveRows,
+    /// The displayed and non-displayed memory are erased
+    ClearScreen,
+    /// The displayed rows are kept and the number of rows in the roll-up window changes
+    ResizeWindow,
 }
 
 /// Text information
@@ -109,6 +247,66 @@ pub struct Text {
     pub channel: Channel,
 }
 
+impl Text {
+    /// The number of columns that the cursor moves forward after displaying this text.
+    ///
+    /// Each character occupies a single column.  A character that needs a backspace replaces the
+    /// previous character and therefore does not move the cursor.
+    pub fn columns_advanced(&self) -> u8 {
+        let n_chars = self.char1.is_some() as u8 + self.char2.is_some() as u8;
+        n_chars.saturating_sub(self.needs_backspace as u8)
+    }
+
+    /// Whether the character is from one of the extended character sets.
+    ///
+    /// Extended characters replace the previous character, see
+    /// [`needs_backspace`](Self::needs_backspace).  Characters from the basic and special character
+    /// sets, e.g. '♪', are added after the previous character.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Text, tables::Channel};
+    /// let text = |c| Text {
+    ///     needs_backspace: false,
+    ///     char1: Some(c),
+    ///     char2: None,
+    ///     channel: Channel::ONE,
+    /// };
+    /// assert!(text('É').is_extended());
+    /// assert!(!text('♪').is_extended());
+    /// assert!(!text('A').is_extended());
+    /// ```
+    pub fn is_extended(&self) -> bool {
+        self.char1
+            .and_then(|c| Code::from_char(c, self.channel))
+            .is_some_and(|code| code.needs_backspace())
+    }
+}
+
+/// The text attributes of a run of text.  See [`Cea608Writer::push_styled`].
+///
+/// The default is white text without underline and italics.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StyleAttrs {
+    /// The text color
+    pub color: tables::Color,
+    /// Whether the text is underlined
+    pub underline: bool,
+    /// Whether the text is in italics
+    pub italics: bool,
+}
+
+impl Default for StyleAttrs {
+    fn default() -> Self {
+        let (color, underline, italics) = PreambleAddressCode::default_attributes();
+        Self {
+            color,
+            underline,
+            italics,
+        }
+    }
+}
+
 /// CEA-08 information
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Cea608 {
@@ -135,6 +333,27 @@ pub enum Cea608 {
     Preamble(Channel, PreambleAddressCode),
     /// A mid-row was received
     MidRowChange(Channel, MidRow),
+    /// A control code that is not otherwise handled, e.g. an unknown control code or the alarm
+    /// codes.  Contains the [`Field`] signalled by the control code, if any, and the original
+    /// byte pair including parity.  Only produced when enabled with
+    /// [`Cea608State::set_raw_controls`].
+    RawControl(Channel, Option<Field>, [u8; 2]),
+    /// Data for the Text service of a [`Channel`], received after a
+    /// [`TextRestart`](tables::Control::TextRestart) or
+    /// [`ResumeTextDisplay`](tables::Control::ResumeTextDisplay) control code until a caption
+    /// mode is selected again.  See [`Cea608State::decode`].
+    TextService(Channel, TextServiceData),
+}
+
+/// The data of a [`Cea608::TextService`] event
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextServiceData {
+    /// Text for the Text service
+    Text(Text),
+    /// A control code for the Text service, including the
+    /// [`TextRestart`](tables::Control::TextRestart) or
+    /// [`ResumeTextDisplay`](tables::Control::ResumeTextDisplay) that selected it
+    Control(tables::Control),
 }
 
 impl Cea608 {
@@ -152,45 +371,801 @@ impl Cea608 {
             Self::Preamble(chan, _) => *chan,
             Self::MidRowChange(chan, _) => *chan,
             Self::DeleteToEndOfRow(chan) => *chan,
+            Self::RawControl(chan, _, _) => *chan,
+            Self::TextService(chan, _) => *chan,
+        }
+    }
+
+    /// The row for events that position the cursor on a specific row.  Only
+    /// [`Preamble`](Self::Preamble) events have a row, other events return None.
+    pub fn row(&self) -> Option<u8> {
+        match self {
+            Self::Preamble(_, preamble) => Some(preamble.row()),
+            _ => None,
+        }
+    }
+
+    /// The number of columns that the cursor moves forward when processing this event.
+    ///
+    /// - [`Text`](Self::Text) advances by the number of displayed characters, see
+    ///   [`Text::columns_advanced`].
+    /// - [`MidRowChange`](Self::MidRowChange) is displayed as a space and advances by one.
+    /// - [`TabOffset`](Self::TabOffset) advances by the signalled offset.
+    /// - All other events do not advance the cursor.  Note that some of them (e.g.
+    ///   [`Preamble`](Self::Preamble), [`Backspace`](Self::Backspace) or
+    ///   [`CarriageReturn`](Self::CarriageReturn)) may still reposition the cursor.
+    pub fn columns_advanced(&self) -> u8 {
+        match self {
+            Self::Text(text) => text.columns_advanced(),
+            Self::MidRowChange(_, _) => 1,
+            Self::TabOffset(_, offset) => *offset,
+            _ => 0,
+        }
+    }
+
+    /// Whether this event moves the cursor forward.  See [`columns_advanced`](Self::columns_advanced)
+    /// for the rules.
+    pub fn advances_cursor(&self) -> bool {
+        self.columns_advanced() > 0
+    }
+
+    /// The [`Control`](tables::Control) code that produces this event.
+    ///
+    /// [`NewMode`](Self::NewMode) maps to the control code selecting the mode, see
+    /// [`Mode::to_control`].  [`Text`](Self::Text) returns None, even for special and extended
+    /// characters that are transmitted as control codes.  A [`TabOffset`](Self::TabOffset) with an
+    /// offset outside of 1 to 3 also returns None.  [`RawControl`](Self::RawControl) returns the
+    /// control code parsed from the original byte pair.  [`TextService`](Self::TextService)
+    /// returns the control code of [`TextServiceData::Control`] and None for text.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608, Mode, tables::{Channel, Control}};
+    /// let cea608 = Cea608::NewMode(Channel::ONE, Mode::RollUp3);
+    /// assert_eq!(cea608.control(), Some(Control::RollUp3));
+    /// ```
+    pub fn control(&self) -> Option<tables::Control> {
+        Some(match self {
+            Self::Text(_) => return None,
+            Self::NewMode(_, mode) => mode.to_control(),
+            Self::EraseDisplay(_) => tables::Control::EraseDisplayedMemory,
+            Self::EraseNonDisplay(_) => tables::Control::EraseNonDisplayedMemory,
+            Self::CarriageReturn(_) => tables::Control::CarriageReturn,
+            Self::Backspace(_) => tables::Control::Backspace,
+            Self::EndOfCaption(_) => tables::Control::EndOfCaption,
+            Self::TabOffset(_, offset) => return tables::Control::tab_offset(*offset),
+            Self::DeleteToEndOfRow(_) => tables::Control::DeleteToEndOfRow,
+            Self::Preamble(_, preamble) => tables::Control::PreambleAddress(*preamble),
+            Self::MidRowChange(_, midrow) => tables::Control::MidRow(*midrow),
+            Self::RawControl(_, _, data) => match Code::from_data(*data) {
+                Ok([Code::Control(control_code), _]) => control_code.code(),
+                _ => return None,
+            },
+            Self::TextService(_, TextServiceData::Control(control)) => *control,
+            Self::TextService(_, TextServiceData::Text(_)) => return None,
+        })
+    }
+
+    /// Whether this event erases any memory, i.e. [`EraseDisplay`](Self::EraseDisplay) and
+    /// [`EraseNonDisplay`](Self::EraseNonDisplay).  These are the events in the
+    /// [`EventCategory::Erase`] category.
+    pub fn is_erase(&self) -> bool {
+        self.category() == EventCategory::Erase
+    }
+
+    /// Whether this event is produced by a control code, i.e. any event except
+    /// [`Text`](Self::Text) and the text of a [`TextService`](Self::TextService).  Special and
+    /// extended characters are transmitted as control codes but produce text and are not
+    /// considered control events.
+    pub fn is_control(&self) -> bool {
+        !matches!(
+            self,
+            Self::Text(_) | Self::TextService(_, TextServiceData::Text(_))
+        )
+    }
+
+    /// The broad [`EventCategory`] of this event
+    pub fn category(&self) -> EventCategory {
+        match self {
+            Self::Text(_) => EventCategory::Text,
+            Self::Preamble(_, _) => EventCategory::Positioning,
+            Self::MidRowChange(_, _) => EventCategory::Styling,
+            Self::EraseDisplay(_) | Self::EraseNonDisplay(_) => EventCategory::Erase,
+            Self::NewMode(_, _) | Self::EndOfCaption(_) => EventCategory::ModeChange,
+            Self::TabOffset(_, _)
+            | Self::Backspace(_)
+            | Self::CarriageReturn(_)
+            | Self::DeleteToEndOfRow(_) => EventCategory::Cursor,
+            Self::RawControl(_, _, _) => EventCategory::Other,
+            Self::TextService(_, _) => EventCategory::TextService,
+        }
+    }
+}
+
+/// Broad categories of [`Cea608`] events.  See [`Cea608::category`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EventCategory {
+    /// [`Cea608::Text`]
+    Text,
+    /// [`Cea608::Preamble`]
+    Positioning,
+    /// [`Cea608::MidRowChange`]
+    Styling,
+    /// [`Cea608::EraseDisplay`] and [`Cea608::EraseNonDisplay`]
+    Erase,
+    /// [`Cea608::NewMode`] and [`Cea608::EndOfCaption`]
+    ModeChange,
+    /// [`Cea608::TabOffset`], [`Cea608::Backspace`], [`Cea608::CarriageReturn`] and
+    /// [`Cea608::DeleteToEndOfRow`]
+    Cursor,
+    /// [`Cea608::RawControl`]
+    Other,
+    /// [`Cea608::TextService`]
+    TextService,
+}
+
+/// Whether two adjacent events erase both the displayed and the non-displayed memory of a
+/// channel.
+///
+/// This is the case for a [`Cea608::EraseDisplay`] and a [`Cea608::EraseNonDisplay`] on the same
+/// [`Channel`], in either order.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Cea608, is_full_clear, tables::Channel};
+/// let edm = Cea608::EraseDisplay(Channel::ONE);
+/// let enm = Cea608::EraseNonDisplay(Channel::ONE);
+/// assert!(is_full_clear(&edm, &enm));
+/// assert!(!is_full_clear(&edm, &Cea608::EraseNonDisplay(Channel::TWO)));
+/// ```
+pub fn is_full_clear(prev: &Cea608, cur: &Cea608) -> bool {
+    matches!(
+        (prev, cur),
+        (Cea608::EraseDisplay(a), Cea608::EraseNonDisplay(b))
+            | (Cea608::EraseNonDisplay(a), Cea608::EraseDisplay(b))
+            if a == b
+    )
+}
+
+/// Split a sequence of [`Cea608`] events into the events for [`Channel::ONE`] and the events for
+/// [`Channel::TWO`].
+///
+/// The relative order of the events within each channel is kept.
+pub fn split_by_channel(events: &[Cea608]) -> (Vec<Cea608>, Vec<Cea608>) {
+    events
+        .iter()
+        .partition(|cea608| cea608.channel() == Channel::ONE)
+}
+
+/// Whether any of the byte pairs produce displayable text.
+///
+/// Padding and control codes that do not display a character are ignored, as are byte pairs that
+/// fail to decode.  As with [`Cea608State`], text is only considered after a control code has
+/// signalled the channel.  All byte pairs must be from the same [`Field`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::has_captions;
+/// // RU2 followed by padding
+/// assert!(!has_captions(&[[0x94, 0x25], [0x80, 0x80]]));
+/// // RU2 followed by 'A'
+/// assert!(has_captions(&[[0x94, 0x25], [0xc1, 0x80]]));
+/// ```
+pub fn has_captions(pairs: &[[u8; 2]]) -> bool {
+    let mut state = Cea608State::default();
+    pairs
+        .iter()
+        .any(|data| matches!(state.decode(*data), Ok(Some(Cea608::Text(_)))))
+}
+
+/// Remove padding byte pairs that do not affect the decoded captions.
+///
+/// Leading and trailing padding is always removed.  If `collapse_interior` is set, runs of
+/// padding between two different byte pairs are also removed.  Padding between two identical
+/// byte pairs is always kept as it determines whether the second copy of a control code is a
+/// new command or a duplicate, see [`Cea608State::set_dedup_window`].
+///
+/// Only the timing of the data is changed, the decoded [`Cea608`] events are unchanged.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::compact;
+/// let pad = [0x80, 0x80];
+/// let edm = [0x94, 0x2c];
+/// let pairs = [pad, edm, pad, edm, pad, pad, [0xc1, 0x80], pad];
+/// assert_eq!(compact(&pairs, false), &pairs[1..7]);
+/// assert_eq!(compact(&pairs, true), [edm, pad, edm, [0xc1, 0x80]]);
+/// ```
+pub fn compact(pairs: &[[u8; 2]], collapse_interior: bool) -> Vec<[u8; 2]> {
+    const PADDING: [u8; 2] = [0x80, 0x80];
+    let Some(start) = pairs.iter().position(|data| *data != PADDING) else {
+        return vec![];
+    };
+    let end = pairs.iter().rposition(|data| *data != PADDING).unwrap() + 1;
+    let pairs = &pairs[start..end];
+    if !collapse_interior {
+        return pairs.to_vec();
+    }
+
+    let mut ret = Vec::with_capacity(pairs.len());
+    let mut i = 0;
+    while i < pairs.len() {
+        let run = pairs[i..]
+            .iter()
+            .take_while(|data| **data == PADDING)
+            .count();
+        if run == 0 {
+            ret.push(pairs[i]);
+            i += 1;
+            continue;
+        }
+        // the run is interior so there is always data on both sides
+        if pairs[i - 1] == pairs[i + run] {
+            ret.extend_from_slice(&pairs[i..i + run]);
+        }
+        i += run;
+    }
+    ret
+}
+
+/// The caption services that contain displayable text in a capture of both fields.
+///
+/// Each field is decoded separately and an [`Id`] is reported when any text is decoded for its
+/// [`Channel`], see [`has_captions`].  The returned ids are ordered from [`Id::CC1`] to
+/// [`Id::CC4`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{detect_services, Id};
+/// // RU2 followed by 'A' on channel 2 of field 1 and RU2 without text on channel 1 of field 2
+/// let field1 = [[0x1c, 0x25], [0xc1, 0x80]];
+/// let field2 = [[0x15, 0x25], [0x80, 0x80]];
+/// assert_eq!(detect_services(&field1, &field2), [Id::CC2]);
+/// ```
+pub fn detect_services(field1: &[[u8; 2]], field2: &[[u8; 2]]) -> Vec<Id> {
+    let mut found = [false; 4];
+    for (field, pairs) in [(Field::ONE, field1), (Field::TWO, field2)] {
+        let mut state = Cea608State::default();
+        for data in pairs {
+            if let Ok(Some(Cea608::Text(text))) = state.decode(*data) {
+                let id = Id::from_caption_field_channel(field, text.channel);
+                found[id as usize] = true;
+            }
         }
     }
+    [Id::CC1, Id::CC2, Id::CC3, Id::CC4]
+        .into_iter()
+        .filter(|id| found[*id as usize])
+        .collect()
+}
+
+/// Produce only the control events from the result of decoding each byte pair, tagged with the
+/// index of the byte pair, e.g. for inspecting the command structure of a stream without the
+/// text.  See [`Cea608::is_control`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{control_timeline, Cea608State, tables::Control};
+/// let mut state = Cea608State::default();
+/// // RU2, RU2, 'A', CR
+/// let pairs = [[0x94, 0x25], [0x94, 0x25], [0xc1, 0x80], [0x94, 0xad]];
+/// let timeline = control_timeline(pairs.iter().map(|data| state.decode(*data).unwrap()));
+/// assert_eq!(
+///     timeline.map(|(idx, cea608)| (idx, cea608.control().unwrap())).collect::<Vec<_>>(),
+///     [(0, Control::RollUp2), (3, Control::CarriageReturn)]
+/// );
+/// ```
+pub fn control_timeline<I: IntoIterator<Item = Option<Cea608>>>(
+    iter: I,
+) -> ControlTimeline<I::IntoIter> {
+    ControlTimeline {
+        iter: iter.into_iter().enumerate(),
+    }
+}
+
+/// Iterator returned by [`control_timeline`]
+#[derive(Debug)]
+pub struct ControlTimeline<I> {
+    iter: std::iter::Enumerate<I>,
+}
+
+impl<I: Iterator<Item = Option<Cea608>>> Iterator for ControlTimeline<I> {
+    type Item = (usize, Cea608);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.by_ref().find_map(|(idx, cea608)| {
+            cea608
+                .filter(|cea608| cea608.is_control())
+                .map(|cea608| (idx, cea608))
+        })
+    }
+}
+
+/// Merge the timestamped events decoded from field 1 and field 2 into a single stream ordered by
+/// timestamp.  Each event is tagged with the [`Id`] of its field and channel.
+///
+/// Both inputs must already be ordered by timestamp.  Events with equal timestamps are produced
+/// with field 1 first.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{merge_fields, Cea608, Id, tables::Channel};
+/// let field1 = [(0, Cea608::EraseDisplay(Channel::ONE)), (2, Cea608::EndOfCaption(Channel::ONE))];
+/// let field2 = [(1, Cea608::EraseDisplay(Channel::TWO)), (2, Cea608::CarriageReturn(Channel::ONE))];
+/// let merged = merge_fields(field1, field2).collect::<Vec<_>>();
+/// assert_eq!(
+///     merged,
+///     [
+///         (0, Id::CC1, Cea608::EraseDisplay(Channel::ONE)),
+///         (1, Id::CC4, Cea608::EraseDisplay(Channel::TWO)),
+///         (2, Id::CC1, Cea608::EndOfCaption(Channel::ONE)),
+///         (2, Id::CC3, Cea608::CarriageReturn(Channel::ONE)),
+///     ]
+/// );
+/// ```
+pub fn merge_fields<T, I1, I2>(field1: I1, field2: I2) -> MergeFields<T, I1::IntoIter, I2::IntoIter>
+where
+    T: Ord,
+    I1: IntoIterator<Item = (T, Cea608)>,
+    I2: IntoIterator<Item = (T, Cea608)>,
+{
+    MergeFields {
+        field1: field1.into_iter().peekable(),
+        field2: field2.into_iter().peekable(),
+    }
+}
+
+/// Iterator returned by [`merge_fields`]
+#[derive(Debug)]
+pub struct MergeFields<T, I1: Iterator<Item = (T, Cea608)>, I2: Iterator<Item = (T, Cea608)>> {
+    field1: std::iter::Peekable<I1>,
+    field2: std::iter::Peekable<I2>,
+}
+
+impl<T, I1, I2> Iterator for MergeFields<T, I1, I2>
+where
+    T: Ord,
+    I1: Iterator<Item = (T, Cea608)>,
+    I2: Iterator<Item = (T, Cea608)>,
+{
+    type Item = (T, Id, Cea608);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = match (self.field1.peek(), self.field2.peek()) {
+            (Some((ts1, _)), Some((ts2, _))) if ts2 < ts1 => Field::TWO,
+            (None, Some(_)) => Field::TWO,
+            (Some(_), _) => Field::ONE,
+            (None, None) => return None,
+        };
+        let (timestamp, cea608) = if field == Field::ONE {
+            self.field1.next()?
+        } else {
+            self.field2.next()?
+        };
+        let id = Id::from_caption_field_channel(field, cea608.channel());
+        Some((timestamp, id, cea608))
+    }
 }
 
 /// Helper struct that has two purposes:
 /// 1. Tracks the previous data for control code de-duplication
 /// 2. Adds the last received channel to non control codes.
 ///
-/// This object only keeps data for a single [`Field`]
-#[derive(Debug, Default)]
+/// This object only keeps data for a single [`Field`].  Feeding data from both fields into the
+/// same object will mix up the channel tracking and result in misattributed data.  Only control
+/// codes that signal a field are de-duplicated separately for each field, see
+/// [`last_data`](Self::last_data).  Use a separate object for each field or enable
+/// [`set_auto_reset_on_field_change`](Self::set_auto_reset_on_field_change).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Cea608State {
-    last_data: Option<[u8; 2]>,
+    last_data: [Option<[u8; 2]>; 2],
     last_channel: Option<Channel>,
     last_received_field: Option<Field>,
+    n_padding: usize,
+    max_dedup_padding: usize,
+    dedup_disabled: bool,
+    auto_reset_on_field_change: bool,
+    initial_control_is_tail: bool,
+    transcripts: Option<[String; 2]>,
+    transcript_cursors: [TranscriptCursor; 2],
+    text_mode: [bool; 2],
+    raw_controls: bool,
+    modes: [Option<Mode>; 2],
+    n_texts_without_mode: [usize; 2],
+    channel_inheritance: ChannelInheritance,
+    n_pairs_since_control: usize,
+    stats: stats::Stats,
+    drop_callback: DropCallback,
+}
+
+/// How text is assigned the channel of the last control code.  See
+/// [`Cea608State::set_channel_inheritance`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ChannelInheritance {
+    /// Text is always assigned the channel of the last control code
+    #[default]
+    Always,
+    /// Text is only assigned the channel of the last control code if it is received at most
+    /// this many byte pairs after that control code
+    WithinWindow(usize),
+    /// Text is never assigned the channel of the last control code
+    Never,
+}
+
+/// The reason a byte pair did not produce an event.  See [`Cea608State::set_drop_callback`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DropReason {
+    /// The byte pair is a repeated control code
+    Duplicate,
+    /// The byte pair is padding
+    Padding,
+    /// The byte pair does not contain any known characters, or is a control code that does not
+    /// produce an event
+    Unknown,
+    /// The byte pair contains text but its channel is unknown, e.g. because no control code has
+    /// signalled a channel yet.  See [`Cea608State::set_channel_inheritance`].
+    NoChannel,
+}
+
+type DropCallbackFn = dyn FnMut(DropReason, [u8; 2]) + Send;
+
+#[derive(Clone, Default)]
+struct DropCallback(Option<Arc<Mutex<DropCallbackFn>>>);
+
+impl DropCallback {
+    fn call(&self, reason: DropReason, data: [u8; 2]) {
+        if let Some(callback) = self.0.as_ref() {
+            let mut callback = callback.lock().unwrap_or_else(|err| err.into_inner());
+            callback(reason, data);
+        }
+    }
+}
+
+impl std::fmt::Debug for DropCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("DropCallback")
+            .field(&self.0.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl PartialEq for DropCallback {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.0.as_ref(), other.0.as_ref()) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
+}
+
+impl Eq for DropCallback {}
+
+/// The position of the cursor within the current line of a [`Cea608State::transcript`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TranscriptCursor {
+    /// The row of the current line, if set by a preamble
+    pub row: Option<u8>,
+    /// The cursor column
+    pub column: u8,
+    /// The column of each character on the current line
+    pub line_columns: Vec<u8>,
+}
+
+impl TranscriptCursor {
+    fn backspace(&mut self, transcript: &mut String) {
+        self.column = self.column.saturating_sub(1);
+        if self.line_columns.last() == Some(&self.column) {
+            self.line_columns.pop();
+            transcript.pop();
+        }
+    }
+
+    fn update(&mut self, transcript: &mut String, cea608: &Cea608) {
+        match cea608 {
+            Cea608::Text(text) => {
+                if text.needs_backspace {
+                    self.backspace(transcript);
+                }
+                for c in text.char1.into_iter().chain(text.char2) {
+                    transcript.push(c);
+                    self.line_columns.push(self.column);
+                    self.column = (self.column + 1).min(screen::N_COLUMNS - 1);
+                }
+            }
+            Cea608::Backspace(_) => self.backspace(transcript),
+            Cea608::DeleteToEndOfRow(_) => {
+                while self
+                    .line_columns
+                    .last()
+                    .is_some_and(|column| *column >= self.column)
+                {
+                    self.line_columns.pop();
+                    transcript.pop();
+                }
+            }
+            Cea608::MidRowChange(_, _) | Cea608::TabOffset(_, _) => {
+                self.column = self
+                    .column
+                    .saturating_add(cea608.columns_advanced())
+                    .min(screen::N_COLUMNS - 1);
+            }
+            Cea608::Preamble(_, preamble) if self.row == Some(preamble.row()) => {
+                self.column = preamble.column();
+            }
+            Cea608::CarriageReturn(_) | Cea608::Preamble(_, _) => {
+                if !transcript.is_empty() && !transcript.ends_with('\n') {
+                    transcript.push('\n');
+                }
+                self.line_columns.clear();
+                (self.row, self.column) = match cea608 {
+                    Cea608::Preamble(_, preamble) => (Some(preamble.row()), preamble.column()),
+                    _ => (None, 0),
+                };
+            }
+            _ => (),
+        }
+    }
 }
 
 impl Cea608State {
+    /// Construct a [`Cea608StateBuilder`] for configuring a new [`Cea608State`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::Cea608State;
+    /// let state = Cea608State::builder()
+    ///     .dedup_window(2)
+    ///     .transcript_enabled(true)
+    ///     .build();
+    /// assert_eq!(state.dedup_window(), 2);
+    /// assert!(state.transcript_enabled());
+    /// ```
+    pub fn builder() -> Cea608StateBuilder {
+        Cea608StateBuilder::default()
+    }
+
     /// Decode the provided bytes into an optional parsed [`Cea608`] command.
+    ///
+    /// After a [`TextRestart`](tables::Control::TextRestart) or
+    /// [`ResumeTextDisplay`](tables::Control::ResumeTextDisplay) control code, all data for that
+    /// [`Channel`] is produced as [`Cea608::TextService`] until a caption mode is selected again.
     pub fn decode(&mut self, data: [u8; 2]) -> Result<Option<Cea608>, ParserError> {
-        trace!("decoding {data:x?}, last data {:x?}", self.last_data);
+        self.decode_with_fallback(data, None)
+    }
+
+    /// Decode the provided bytes into an optional parsed [`Cea608`] command, assigning text to
+    /// `channel` if no control code has signalled a channel yet.
+    ///
+    /// [`decode`](Self::decode) drops text received before any control code as its channel is
+    /// unknown.  If the channel is known out of band, e.g. when joining a stream in the middle of
+    /// a caption, this allows decoding that text.  Control codes still update the tracked channel
+    /// as usual, and `channel` is not stored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608, Cea608State, Text, tables::Channel};
+    /// let mut state = Cea608State::default();
+    /// assert_eq!(state.decode([0xc1, 0x80]), Ok(None));
+    /// assert_eq!(
+    ///     state.decode_with_channel([0xc1, 0x80], Channel::TWO),
+    ///     Ok(Some(Cea608::Text(Text {
+    ///         needs_backspace: false,
+    ///         char1: Some('A'),
+    ///         char2: None,
+    ///         channel: Channel::TWO,
+    ///     })))
+    /// );
+    /// ```
+    pub fn decode_with_channel(
+        &mut self,
+        data: [u8; 2],
+        channel: Channel,
+    ) -> Result<Option<Cea608>, ParserError> {
+        self.decode_with_fallback(data, Some(channel))
+    }
+
+    fn decode_with_fallback(
+        &mut self,
+        data: [u8; 2],
+        fallback_channel: Option<Channel>,
+    ) -> Result<Option<Cea608>, ParserError> {
+        self.stats.record_data(data);
+        let ret = self.decode_data(data, fallback_channel)?;
+        if let Some(cea608) = ret.as_ref() {
+            self.stats.record(cea608);
+        }
+        match ret {
+            Some(Cea608::NewMode(channel, mode)) => {
+                self.modes[channel.id() as usize - 1] = Some(mode)
+            }
+            Some(Cea608::Text(text)) if self.modes[text.channel.id() as usize - 1].is_none() => {
+                let idx = text.channel.id() as usize - 1;
+                if self.n_texts_without_mode[idx] == 0 {
+                    debug!("Text received on {:?} without a mode", text.channel);
+                }
+                self.n_texts_without_mode[idx] += 1;
+            }
+            _ => (),
+        }
+        if let (Some(transcripts), Some(cea608)) = (self.transcripts.as_mut(), ret.as_ref()) {
+            let idx = cea608.channel().id() as usize - 1;
+            self.transcript_cursors[idx].update(&mut transcripts[idx], cea608);
+        }
+        Ok(ret)
+    }
+
+    /// Decode the provided bytes into an optional parsed [`Cea608`] command together with the
+    /// [`Field`] signalled by the control code that produced it.
+    ///
+    /// Only some control codes signal a field, see [`ControlCode::field`](tables::ControlCode::field).
+    /// The field is None for [`Cea608::Text`] and for control codes that do not signal a field,
+    /// e.g. preambles and mid-row codes.  Use [`last_received_field`](Self::last_received_field)
+    /// for the last field signalled by any control code.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608, Cea608State, tables::{Channel, Field}};
+    /// let mut state = Cea608State::default();
+    /// // EDM on CC3
+    /// assert_eq!(
+    ///     state.decode_with_field([0x15, 0x2c]),
+    ///     Ok(Some((Cea608::EraseDisplay(Channel::ONE), Some(Field::TWO))))
+    /// );
+    /// ```
+    pub fn decode_with_field(
+        &mut self,
+        data: [u8; 2],
+    ) -> Result<Option<(Cea608, Option<Field>)>, ParserError> {
+        let Some(cea608) = self.decode(data)? else {
+            return Ok(None);
+        };
+        let field = match Code::from_data(data) {
+            Ok([Code::Control(control_code), _]) if cea608.is_control() => control_code.field(),
+            _ => None,
+        };
+        Ok(Some((cea608, field)))
+    }
+
+    /// Decode the provided bytes into an optional parsed [`Cea608`] command tagged with the
+    /// provided timestamp.
+    ///
+    /// The timestamp can be of any type, e.g. a presentation timestamp in nanoseconds or a frame
+    /// number, and is returned unchanged together with the decoded command.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608, Cea608State, tables::Channel};
+    /// let mut state = Cea608State::default();
+    /// assert_eq!(
+    ///     state.decode_at([0x94, 0x2c], 1_000_000_000u64),
+    ///     Ok(Some((Cea608::EraseDisplay(Channel::ONE), 1_000_000_000)))
+    /// );
+    /// ```
+    pub fn decode_at<T>(
+        &mut self,
+        data: [u8; 2],
+        timestamp: T,
+    ) -> Result<Option<(Cea608, T)>, ParserError> {
+        Ok(self.decode(data)?.map(|cea608| (cea608, timestamp)))
+    }
+
+    /// Decode the provided bytes into a [`Token`] that keeps the original byte pair.
+    ///
+    /// Every byte pair produces a token, including padding, duplicate control codes and byte pairs
+    /// that fail to decode.  Pushing the tokens into a [`Cea608Writer`] with
+    /// [`push_token`](Cea608Writer::push_token) reproduces the original byte stream exactly, which
+    /// allows editing a stream and re-emitting the unchanged parts byte for byte.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608, Cea608State, Token, tables::Channel};
+    /// let mut state = Cea608State::default();
+    /// let edm = Cea608::EraseDisplay(Channel::ONE);
+    /// assert_eq!(
+    ///     state.decode_token([0x94, 0x2c]),
+    ///     Token::Event { event: edm, data: [0x94, 0x2c] }
+    /// );
+    /// assert_eq!(state.decode_token([0x94, 0x2c]), Token::Skipped([0x94, 0x2c]));
+    /// ```
+    pub fn decode_token(&mut self, data: [u8; 2]) -> Token {
+        match self.decode(data) {
+            Ok(Some(event)) => Token::Event { event, data },
+            Ok(None) => Token::Skipped(data),
+            Err(error) => Token::Invalid { data, error },
+        }
+    }
+
+    fn decode_data(
+        &mut self,
+        data: [u8; 2],
+        fallback_channel: Option<Channel>,
+    ) -> Result<Option<Cea608>, ParserError> {
         let code = Code::from_data(data)?;
 
-        if Some(data) == self.last_data {
+        if self.auto_reset_on_field_change {
+            if let [Code::Control(control_code), _] = code {
+                if let (Some(field), Some(last_field)) =
+                    (control_code.field(), self.last_received_field)
+                {
+                    if field != last_field {
+                        debug!("Field changed from {last_field:?} to {field:?}, resetting");
+                        self.reset_field_tracking();
+                    }
+                }
+            }
+        }
+
+        // control codes are de-duplicated separately for each field
+        let field = match code {
+            [Code::Control(control_code), _] => control_code.field(),
+            _ => None,
+        }
+        .or(self.last_received_field)
+        .unwrap_or(Field::ONE);
+        let last_data = &mut self.last_data[field.id() as usize - 1];
+        trace!("decoding {data:x?}, last data {last_data:x?} for {field:?}");
+
+        if !self.dedup_disabled && Some(data) == *last_data {
             if let Code::Control(_control) = code[0] {
                 debug!("Skipping duplicate");
-                return Ok(None);
+                return self.dropped(DropReason::Duplicate, data);
             }
         }
-        self.last_data = Some(data);
+        // the second copy of a doubled control code does not count
+        self.n_pairs_since_control = self.n_pairs_since_control.saturating_add(1);
+        if code == [Code::NUL, Code::NUL] && self.n_padding < self.max_dedup_padding {
+            // keep the last data around for de-duplicating a control code after padding
+            self.n_padding += 1;
+            return self.dropped(DropReason::Padding, data);
+        }
+        let initial_tail = self.initial_control_is_tail
+            && self.last_data == [None; 2]
+            && self.last_channel.is_none()
+            && matches!(code[0], Code::Control(_));
+        if initial_tail {
+            debug!("Treating initial control as the second copy of a doubled control");
+        } else {
+            self.last_data[field.id() as usize - 1] = Some(data);
+        }
+        self.n_padding = 0;
 
-        // TODO: handle xds and text mode
+        // TODO: handle xds
+
+        if let [Code::Control(control_code), _] = code {
+            let text_mode = &mut self.text_mode[control_code.channel().id() as usize - 1];
+            match control_code.code() {
+                tables::Control::TextRestart | tables::Control::ResumeTextDisplay => {
+                    debug!("Entering text mode on {:?}", control_code.channel());
+                    *text_mode = true;
+                }
+                control if control.to_mode().is_some() => *text_mode = false,
+                _ => (),
+            }
+        }
 
         match code {
             [Code::Control(control_code), _] => {
                 let channel = control_code.channel();
                 self.last_channel = Some(channel);
+                self.n_pairs_since_control = 0;
                 if let Some(field) = control_code.field() {
                     self.last_received_field = Some(field);
                 }
+                if let Some(mode) = control_code.code().to_mode() {
+                    return Ok(Some(Cea608::NewMode(channel, mode)));
+                }
+                if self.text_mode[channel.id() as usize - 1] {
+                    let data = match code[0].char() {
+                        Some(char) => TextServiceData::Text(Text {
+                            needs_backspace: code[0].needs_backspace(),
+                            char1: Some(char),
+                            char2: None,
+                            channel,
+                        }),
+                        None => TextServiceData::Control(control_code.code()),
+                    };
+                    return Ok(Some(Cea608::TextService(channel, data)));
+                }
                 Ok(Some(match control_code.code() {
                     tables::Control::MidRow(midrow) => Cea608::MidRowChange(channel, midrow),
                     tables::Control::PreambleAddress(preamble) => {
@@ -201,18 +1176,11 @@ impl Cea608State {
                     tables::Control::CarriageReturn => Cea608::CarriageReturn(channel),
                     tables::Control::Backspace => Cea608::Backspace(channel),
                     tables::Control::EndOfCaption => Cea608::EndOfCaption(channel),
-                    tables::Control::RollUp2 => Cea608::NewMode(channel, Mode::RollUp2),
-                    tables::Control::RollUp3 => Cea608::NewMode(channel, Mode::RollUp3),
-                    tables::Control::RollUp4 => Cea608::NewMode(channel, Mode::RollUp4),
-                    tables::Control::ResumeDirectionCaptioning => {
-                        Cea608::NewMode(channel, Mode::PaintOn)
-                    }
-                    tables::Control::ResumeCaptionLoading => Cea608::NewMode(channel, Mode::PopOn),
                     tables::Control::TabOffset1 => Cea608::TabOffset(channel, 1),
                     tables::Control::TabOffset2 => Cea608::TabOffset(channel, 2),
                     tables::Control::TabOffset3 => Cea608::TabOffset(channel, 3),
                     tables::Control::DeleteToEndOfRow => Cea608::DeleteToEndOfRow(channel),
-                    // TODO: TextRestart, ResumeTextDisplay
+                    // TextRestart, ResumeTextDisplay are handled above
                     _ => {
                         if let Some(char) = code[0].char() {
                             Cea608::Text(Text {
@@ -221,95 +1189,814 @@ impl Cea608State {
                                 char2: None,
                                 channel,
                             })
+                        } else if self.raw_controls {
+                            Cea608::RawControl(channel, control_code.field(), data)
                         } else {
-                            return Ok(None);
+                            return self.dropped(DropReason::Unknown, data);
                         }
                     }
                 }))
             }
             _ => {
-                let Some(channel) = self.last_channel else {
-                    return Ok(None);
-                };
                 let char1 = code[0].char();
                 let char2 = code[1].char();
-                if char1.is_some() || char2.is_some() {
-                    Ok(Some(Cea608::Text(Text {
-                        needs_backspace: false,
-                        char1,
-                        char2,
+                if char1.is_none() && char2.is_none() {
+                    let reason = if code == [Code::NUL, Code::NUL] {
+                        DropReason::Padding
+                    } else {
+                        DropReason::Unknown
+                    };
+                    return self.dropped(reason, data);
+                }
+                let inherited = match self.channel_inheritance {
+                    ChannelInheritance::Always => self.last_channel,
+                    ChannelInheritance::WithinWindow(window) => self
+                        .last_channel
+                        .filter(|_| self.n_pairs_since_control <= window),
+                    ChannelInheritance::Never => None,
+                };
+                let Some(channel) = inherited.or(fallback_channel) else {
+                    return self.dropped(DropReason::NoChannel, data);
+                };
+                let text = Text {
+                    needs_backspace: false,
+                    char1,
+                    char2,
+                    channel,
+                };
+                if self.text_mode[channel.id() as usize - 1] {
+                    return Ok(Some(Cea608::TextService(
                         channel,
-                    })))
-                } else {
-                    Ok(None)
+                        TextServiceData::Text(text),
+                    )));
                 }
+                Ok(Some(Cea608::Text(text)))
             }
         }
     }
 
+    fn dropped(
+        &mut self,
+        reason: DropReason,
+        data: [u8; 2],
+    ) -> Result<Option<Cea608>, ParserError> {
+        trace!("Dropping {data:x?}: {reason:?}");
+        self.drop_callback.call(reason, data);
+        Ok(None)
+    }
+
+    /// Set a callback that is called with the [`DropReason`] whenever a byte pair does not
+    /// produce an event, e.g. for finding out why expected captions are missing.  Replaces any
+    /// previously set callback.
+    ///
+    /// The callback is kept on [`reset`](Self::reset) and shared with any clones of this
+    /// object.  It is not part of a [`StateSnapshot`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::sync::{Arc, Mutex};
+    /// # use cea608_types::{Cea608State, DropReason};
+    /// let dropped = Arc::new(Mutex::new(vec![]));
+    /// let mut state = Cea608State::default();
+    /// let dropped_clone = dropped.clone();
+    /// state.set_drop_callback(move |reason, data| dropped_clone.lock().unwrap().push((reason, data)));
+    /// state.decode([0x80, 0x80]).unwrap();
+    /// assert_eq!(*dropped.lock().unwrap(), [(DropReason::Padding, [0x80, 0x80])]);
+    /// ```
+    pub fn set_drop_callback<F: FnMut(DropReason, [u8; 2]) + Send + 'static>(
+        &mut self,
+        callback: F,
+    ) {
+        self.drop_callback = DropCallback(Some(Arc::new(Mutex::new(callback))));
+    }
+
+    /// Remove the callback set with [`set_drop_callback`](Self::set_drop_callback)
+    pub fn clear_drop_callback(&mut self) {
+        self.drop_callback = DropCallback::default();
+    }
+
     /// The [`Field`] that some specific [`tables::Control`] codes referenced.  Can be used to detect field
     /// reversal of the incoming data.
     pub fn last_received_field(&self) -> Option<Field> {
         self.last_received_field
     }
 
-    /// Reset the state to that of an initially constructed object.
-    pub fn reset(&mut self) {
-        *self = Self::default();
+    /// The last [`Mode`] selected for `channel`, if any
+    pub fn mode(&self, channel: Channel) -> Option<Mode> {
+        self.modes[channel.id() as usize - 1]
     }
-}
 
-/// A writer that handles combining single byte [`Code`]s and double byte [`Code`]s.
-#[derive(Debug, Default)]
-pub struct Cea608Writer {
-    pending: VecDeque<Code>,
-    pending_code: Option<Code>,
-}
+    /// The number of [`Cea608::Text`] events produced for `channel` before any [`Mode`] was
+    /// selected for that channel.
+    ///
+    /// Text without a mode cannot be placed correctly and indicates a stream that was joined in
+    /// the middle of a caption or an encoder that does not select a mode.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608State, tables::Channel};
+    /// let mut state = Cea608State::default();
+    /// // PAC followed by 'A' without a mode
+    /// state.decode([0x94, 0x70]).unwrap();
+    /// state.decode([0xc1, 0x80]).unwrap();
+    /// assert_eq!(state.n_texts_without_mode(Channel::ONE), 1);
+    /// ```
+    pub fn n_texts_without_mode(&self, channel: Channel) -> usize {
+        self.n_texts_without_mode[channel.id() as usize - 1]
+    }
 
-impl Cea608Writer {
-    /// Push a [`Code`] into this writer
-    pub fn push(&mut self, code: Code) {
-        self.pending.push_front(code)
+    /// The last byte pair that was decoded for the [`last_received_field`](Self::last_received_field)
+    /// (or [`Field::ONE`] if no field has been received).  Used for de-duplicating control codes.
+    ///
+    /// The last byte pair is tracked separately for each field so that identical control codes
+    /// in interleaved field 1 and field 2 data are not considered duplicates of each other.  The
+    /// field of a byte pair is the field signalled by its control code, or the
+    /// [`last_received_field`](Self::last_received_field) for byte pairs that do not signal a
+    /// field.
+    pub fn last_data(&self) -> Option<[u8; 2]> {
+        self.last_data[self.current_field().id() as usize - 1]
     }
 
-    /// Pop a [`Code`] from this writer
-    pub fn pop(&mut self) -> [u8; 2] {
-        let mut ret = [0x80; 2];
-        let mut prev = None::<Code>;
+    /// Override the last byte pair that was decoded for the
+    /// [`last_received_field`](Self::last_received_field).  See [`last_data`](Self::last_data).
+    ///
+    /// The next call to [`decode`](Self::decode) will consider `data` as the previously received
+    /// byte pair for the purposes of control code de-duplication.  Mostly useful for debugging and
+    /// testing.
+    pub fn set_last_data(&mut self, data: Option<[u8; 2]>) {
+        self.last_data[self.current_field().id() as usize - 1] = data;
+    }
 
-        if let Some(code) = self.pending_code.take() {
-            code.write_into(&mut ret);
-            return ret;
-        }
+    fn current_field(&self) -> Field {
+        self.last_received_field.unwrap_or(Field::ONE)
+    }
 
-        while let Some(code) = self.pending.pop_back() {
-            if let Some(prev) = prev {
-                if code.byte_len() == 1 {
-                    let mut data = [0; 2];
-                    prev.write_into(&mut ret);
-                    code.write_into(&mut data);
-                    ret[1] = data[0];
-                    return ret;
-                } else if code.needs_backspace() {
-                    self.pending_code = Some(code);
-                    let mut data = [0; 2];
-                    prev.write_into(&mut ret);
-                    Code::Space.write_into(&mut data);
-                    ret[1] = data[0];
-                    return ret;
-                } else {
-                    self.pending_code = Some(code);
-                    prev.write_into(&mut ret);
-                    return ret;
-                }
-            } else if code.needs_backspace() {
-                // all back space needing codes are 2 byte commands
+    /// Set the number of byte pairs (frames) within which a repeated control code is considered a
+    /// duplicate.
+    ///
+    /// With the default value of 1, only a control code that is immediately repeated in the next
+    /// byte pair is skipped.  Larger values allow up to `frames - 1` padding byte pairs to be
+    /// present between the two copies of the control code, e.g. `[ctrl][padding][ctrl]` with a
+    /// value of 2.  A value of 0 is treated as 1.
+    pub fn set_dedup_window(&mut self, frames: usize) {
+        self.max_dedup_padding = frames.saturating_sub(1);
+    }
+
+    /// The number of byte pairs (frames) within which a repeated control code is considered a
+    /// duplicate.
+    pub fn dedup_window(&self) -> usize {
+        self.max_dedup_padding + 1
+    }
+
+    /// Set whether repeated control codes are de-duplicated.  Enabled by default.
+    ///
+    /// Control codes are normally sent twice in a row and the second copy is skipped.  Some
+    /// encoders never double control codes, in which case two identical consecutive control codes
+    /// are two separate commands.  With de-duplication disabled, every control code is produced
+    /// and the [`dedup_window`](Self::dedup_window) and
+    /// [`initial_control_is_tail`](Self::initial_control_is_tail) options have no effect.
+    /// Doubled control codes will then be produced twice.
+    pub fn set_dedup(&mut self, dedup: bool) {
+        self.dedup_disabled = !dedup;
+    }
+
+    /// Whether repeated control codes are de-duplicated
+    pub fn dedup(&self) -> bool {
+        !self.dedup_disabled
+    }
+
+    /// Set whether control codes that are not otherwise handled are produced as
+    /// [`Cea608::RawControl`] instead of being dropped.  Disabled by default.
+    ///
+    /// This allows passing through unknown control codes, e.g. from extensions of the
+    /// specification, byte for byte with [`Cea608Writer::push_raw`].
+    pub fn set_raw_controls(&mut self, raw_controls: bool) {
+        self.raw_controls = raw_controls;
+    }
+
+    /// Whether control codes that are not otherwise handled are produced as
+    /// [`Cea608::RawControl`]
+    pub fn raw_controls(&self) -> bool {
+        self.raw_controls
+    }
+
+    /// Set how text, which does not signal a channel itself, is assigned the channel of the last
+    /// control code.  Text that is not assigned a channel is dropped, unless a channel is
+    /// provided with [`decode_with_channel`](Self::decode_with_channel).
+    ///
+    /// In sparse streams, text received long after the last control code may belong to a
+    /// different channel whose control codes were lost.  Restricting the inheritance avoids
+    /// misattributing that text.  The window of [`ChannelInheritance::WithinWindow`] counts all
+    /// byte pairs, including padding, except the de-duplicated second copy of a doubled control
+    /// code.  Text immediately following a control code, or both copies of a doubled control
+    /// code, is 1 byte pair after it.  Default: [`ChannelInheritance::Always`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608State, ChannelInheritance};
+    /// let mut state = Cea608State::default();
+    /// state.set_channel_inheritance(ChannelInheritance::WithinWindow(1));
+    /// // EDM on CC2, 'A' and 'A' after padding
+    /// assert!(state.decode([0x1c, 0x2c]).unwrap().is_some());
+    /// assert!(state.decode([0xc1, 0x80]).unwrap().is_some());
+    /// assert!(state.decode([0x80, 0x80]).unwrap().is_none());
+    /// assert!(state.decode([0xc1, 0x80]).unwrap().is_none());
+    /// ```
+    pub fn set_channel_inheritance(&mut self, inheritance: ChannelInheritance) {
+        self.channel_inheritance = inheritance;
+    }
+
+    /// How text is assigned the channel of the last control code
+    pub fn channel_inheritance(&self) -> ChannelInheritance {
+        self.channel_inheritance
+    }
+
+    /// The [`Stats`](stats::Stats) of the byte pairs and events decoded since construction or
+    /// [`reset`](Self::reset), e.g. [`max_repeat_run`](stats::Stats::max_repeat_run) for
+    /// detecting a stuck encoder.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::Cea608State;
+    /// let mut state = Cea608State::default();
+    /// // doubled EDM
+    /// state.decode([0x94, 0x2c]).unwrap();
+    /// state.decode([0x94, 0x2c]).unwrap();
+    /// assert_eq!(state.stats().max_repeat_run(), 2);
+    /// assert_eq!(state.stats().n_events(), 1);
+    /// ```
+    pub fn stats(&self) -> &stats::Stats {
+        &self.stats
+    }
+
+    /// Set whether to reset the de-duplication and channel tracking when a control code signals
+    /// a different [`Field`] than the [`last_received_field`](Self::last_received_field).
+    ///
+    /// This object only tracks a single field.  If data from the other field is received, the
+    /// de-duplication and channel tracking would otherwise silently be mixed between the two
+    /// fields.  Other state, e.g. the [`transcript`](Self::transcript) and the
+    /// [`stats`](Self::stats), is kept.  Disabled by default.
+    pub fn set_auto_reset_on_field_change(&mut self, auto_reset: bool) {
+        self.auto_reset_on_field_change = auto_reset;
+    }
+
+    fn reset_field_tracking(&mut self) {
+        self.last_data = [None; 2];
+        self.last_channel = None;
+        self.n_padding = 0;
+    }
+
+    /// Whether the de-duplication and channel tracking is reset when a control code signals a
+    /// different [`Field`].
+    pub fn auto_reset_on_field_change(&self) -> bool {
+        self.auto_reset_on_field_change
+    }
+
+    /// Set whether the first control code received is considered to be the second copy of a
+    /// doubled control code.
+    ///
+    /// When joining a stream at an arbitrary point, the first byte pair may be the second copy of
+    /// a doubled control code.  With this option enabled, that control code is still processed
+    /// but an identical control code immediately following it is considered to be the start of a
+    /// new command instead of a duplicate.  Disabled by default.
+    ///
+    /// The first control code is any control code received before any other data since
+    /// construction or [`reset`](Self::reset).
+    pub fn set_initial_control_is_tail(&mut self, initial_control_is_tail: bool) {
+        self.initial_control_is_tail = initial_control_is_tail;
+    }
+
+    /// Whether the first control code received is considered to be the second copy of a doubled
+    /// control code.
+    pub fn initial_control_is_tail(&self) -> bool {
+        self.initial_control_is_tail
+    }
+
+    /// Set whether to accumulate the text decoded for each channel.  See
+    /// [`transcript`](Self::transcript).  Disabling will remove any accumulated text.
+    pub fn set_transcript_enabled(&mut self, enabled: bool) {
+        if enabled != self.transcripts.is_some() {
+            self.transcripts = enabled.then(Default::default);
+            self.transcript_cursors = Default::default();
+        }
+    }
+
+    /// Whether the text decoded for each channel is accumulated.
+    pub fn transcript_enabled(&self) -> bool {
+        self.transcripts.is_some()
+    }
+
+    /// The text decoded so far for a [`Channel`], if enabled with
+    /// [`set_transcript_enabled`](Self::set_transcript_enabled).
+    ///
+    /// Carriage returns and preambles to a different row start a new line.  The cursor column
+    /// within the current line is tracked from preambles, tab offsets and the received text.  Characters that need a
+    /// backspace and [`Cea608::Backspace`] remove the character before the cursor, and
+    /// [`Cea608::DeleteToEndOfRow`] removes the characters from the cursor to the end of the
+    /// line.  Only characters on the current line are removed.  Use [`screen::Cea608Screen`] for
+    /// an accurate representation of the displayed text.
+    ///
+    /// Returns an empty string if disabled.
+    pub fn transcript(&self, channel: Channel) -> &str {
+        self.transcripts
+            .as_ref()
+            .map_or("", |transcripts| &transcripts[channel.id() as usize - 1])
+    }
+
+    /// Capture the complete internal state, including configuration, e.g. for checkpointing a
+    /// decode.  Use [`from_snapshot`](Self::from_snapshot) to restore it.  The
+    /// [drop callback](Self::set_drop_callback) is not included.
+    pub fn state_snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            last_data: self.last_data,
+            last_channel: self.last_channel,
+            last_received_field: self.last_received_field,
+            n_padding: self.n_padding,
+            dedup: self.dedup(),
+            dedup_window: self.dedup_window(),
+            auto_reset_on_field_change: self.auto_reset_on_field_change,
+            initial_control_is_tail: self.initial_control_is_tail,
+            transcripts: self.transcripts.clone(),
+            transcript_cursors: self.transcript_cursors.clone(),
+            text_mode: self.text_mode,
+            raw_controls: self.raw_controls,
+            modes: self.modes,
+            n_texts_without_mode: self.n_texts_without_mode,
+            channel_inheritance: self.channel_inheritance,
+            n_pairs_since_control: self.n_pairs_since_control,
+            stats: self.stats.clone(),
+        }
+    }
+
+    /// Construct a [`Cea608State`] from a [`StateSnapshot`] produced by
+    /// [`state_snapshot`](Self::state_snapshot).
+    pub fn from_snapshot(snapshot: StateSnapshot) -> Self {
+        let mut state = Self {
+            last_data: snapshot.last_data,
+            last_channel: snapshot.last_channel,
+            last_received_field: snapshot.last_received_field,
+            n_padding: snapshot.n_padding,
+            auto_reset_on_field_change: snapshot.auto_reset_on_field_change,
+            initial_control_is_tail: snapshot.initial_control_is_tail,
+            transcripts: snapshot.transcripts,
+            transcript_cursors: snapshot.transcript_cursors,
+            text_mode: snapshot.text_mode,
+            raw_controls: snapshot.raw_controls,
+            modes: snapshot.modes,
+            n_texts_without_mode: snapshot.n_texts_without_mode,
+            channel_inheritance: snapshot.channel_inheritance,
+            n_pairs_since_control: snapshot.n_pairs_since_control,
+            stats: snapshot.stats,
+            ..Self::default()
+        };
+        state.set_dedup(snapshot.dedup);
+        state.set_dedup_window(snapshot.dedup_window);
+        state
+    }
+
+    /// Reset the state to that of an initially constructed object.
+    ///
+    /// Configuration such as the [`dedup_window`](Self::dedup_window) is kept.
+    ///
+    /// After a reset, the first control code is never considered a duplicate, even if it is
+    /// identical to the byte pair decoded before the reset, unless
+    /// [`set_initial_control_is_tail`](Self::set_initial_control_is_tail) is enabled.  Use
+    /// [`reset_with_last_data`](Self::reset_with_last_data) to de-duplicate against a known
+    /// previous byte pair instead.
+    pub fn reset(&mut self) {
+        *self = Self {
+            max_dedup_padding: self.max_dedup_padding,
+            dedup_disabled: self.dedup_disabled,
+            auto_reset_on_field_change: self.auto_reset_on_field_change,
+            initial_control_is_tail: self.initial_control_is_tail,
+            transcripts: self.transcripts.as_ref().map(|_| Default::default()),
+            raw_controls: self.raw_controls,
+            channel_inheritance: self.channel_inheritance,
+            drop_callback: self.drop_callback.clone(),
+            ..Self::default()
+        };
+    }
+
+    /// Reset the state like [`reset`](Self::reset) and consider `data` as the previously
+    /// received byte pair for the purposes of control code de-duplication.
+    ///
+    /// When resuming a stream at an arbitrary point, the first byte pair may be the second copy
+    /// of a doubled control code whose first copy was `data`.  Unlike
+    /// [`set_last_data`](Self::set_last_data), `data` is stored for the [`Field`] signalled by
+    /// its control code, so that it also applies to field 2 data.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::Cea608State;
+    /// let mut state = Cea608State::default();
+    /// // EDM on CC3
+    /// state.reset_with_last_data([0x15, 0x2c]);
+    /// assert_eq!(state.decode([0x15, 0x2c]), Ok(None));
+    /// ```
+    pub fn reset_with_last_data(&mut self, data: [u8; 2]) {
+        self.reset();
+        let field = match Code::from_data(data) {
+            Ok([Code::Control(control_code), _]) => control_code.field(),
+            _ => None,
+        }
+        .unwrap_or(Field::ONE);
+        self.last_data[field.id() as usize - 1] = Some(data);
+    }
+}
+
+/// The complete internal state of a [`Cea608State`].  See [`Cea608State::state_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateSnapshot {
+    /// The last byte pair that was decoded for field 1 and 2, see [`Cea608State::last_data`]
+    pub last_data: [Option<[u8; 2]>; 2],
+    /// The last channel signalled by a control code
+    pub last_channel: Option<Channel>,
+    /// The last field signalled by a control code, see [`Cea608State::last_received_field`]
+    pub last_received_field: Option<Field>,
+    /// The number of padding byte pairs received since the last byte pair
+    pub n_padding: usize,
+    /// See [`Cea608State::dedup`]
+    pub dedup: bool,
+    /// See [`Cea608State::dedup_window`]
+    pub dedup_window: usize,
+    /// See [`Cea608State::auto_reset_on_field_change`]
+    pub auto_reset_on_field_change: bool,
+    /// See [`Cea608State::initial_control_is_tail`]
+    pub initial_control_is_tail: bool,
+    /// The accumulated text of channel 1 and 2, if enabled.  See [`Cea608State::transcript`]
+    pub transcripts: Option<[String; 2]>,
+    /// The cursor within the current transcript line of channel 1 and 2
+    pub transcript_cursors: [TranscriptCursor; 2],
+    /// Whether channel 1 and 2 are in text mode
+    pub text_mode: [bool; 2],
+    /// See [`Cea608State::raw_controls`]
+    pub raw_controls: bool,
+    /// The mode of channel 1 and 2, see [`Cea608State::mode`]
+    pub modes: [Option<Mode>; 2],
+    /// See [`Cea608State::n_texts_without_mode`]
+    pub n_texts_without_mode: [usize; 2],
+    /// See [`Cea608State::channel_inheritance`]
+    pub channel_inheritance: ChannelInheritance,
+    /// The number of byte pairs received since the last control code
+    pub n_pairs_since_control: usize,
+    /// See [`Cea608State::stats`]
+    pub stats: stats::Stats,
+}
+
+/// A byte pair together with the result of decoding it.  See [`Cea608State::decode_token`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// A byte pair that produced an event
+    Event {
+        /// The decoded event
+        event: Cea608,
+        /// The original byte pair
+        data: [u8; 2],
+    },
+    /// A byte pair that did not produce an event, e.g. padding or the second copy of a doubled
+    /// control code
+    Skipped([u8; 2]),
+    /// A byte pair that could not be decoded
+    Invalid {
+        /// The original byte pair
+        data: [u8; 2],
+        /// The decoding error
+        error: ParserError,
+    },
+}
+
+impl Token {
+    /// The original byte pair of this token
+    pub fn data(&self) -> [u8; 2] {
+        match self {
+            Self::Event { data, .. } => *data,
+            Self::Skipped(data) => *data,
+            Self::Invalid { data, .. } => *data,
+        }
+    }
+
+    /// The decoded event, if any
+    pub fn event(&self) -> Option<Cea608> {
+        match self {
+            Self::Event { event, .. } => Some(*event),
+            _ => None,
+        }
+    }
+}
+
+/// Builder for a [`Cea608State`].  See [`Cea608State::builder`].
+///
+/// Options that are not set keep the same default as [`Cea608State::default`].
+#[derive(Debug, Default, Clone)]
+pub struct Cea608StateBuilder {
+    state: Cea608State,
+}
+
+impl Cea608StateBuilder {
+    /// Whether repeated control codes are de-duplicated.  Default: `true`.  See
+    /// [`Cea608State::set_dedup`].
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.state.set_dedup(dedup);
+        self
+    }
+
+    /// The number of byte pairs (frames) within which a repeated control code is considered a
+    /// duplicate.  Default: 1.  See [`Cea608State::set_dedup_window`].
+    pub fn dedup_window(mut self, frames: usize) -> Self {
+        self.state.set_dedup_window(frames);
+        self
+    }
+
+    /// Whether to reset the state when a control code signals a different [`Field`].  Default:
+    /// `false`.  See [`Cea608State::set_auto_reset_on_field_change`].
+    pub fn auto_reset_on_field_change(mut self, auto_reset: bool) -> Self {
+        self.state.set_auto_reset_on_field_change(auto_reset);
+        self
+    }
+
+    /// Whether the first control code received is considered to be the second copy of a doubled
+    /// control code.  Default: `false`.  See [`Cea608State::set_initial_control_is_tail`].
+    pub fn initial_control_is_tail(mut self, initial_control_is_tail: bool) -> Self {
+        self.state
+            .set_initial_control_is_tail(initial_control_is_tail);
+        self
+    }
+
+    /// Whether to accumulate the text decoded for each channel.  Default: `false`.  See
+    /// [`Cea608State::set_transcript_enabled`].
+    pub fn transcript_enabled(mut self, enabled: bool) -> Self {
+        self.state.set_transcript_enabled(enabled);
+        self
+    }
+
+    /// Whether control codes that are not otherwise handled are produced as
+    /// [`Cea608::RawControl`].  Default: `false`.  See [`Cea608State::set_raw_controls`].
+    pub fn raw_controls(mut self, raw_controls: bool) -> Self {
+        self.state.set_raw_controls(raw_controls);
+        self
+    }
+
+    /// How text is assigned the channel of the last control code.  Default:
+    /// [`ChannelInheritance::Always`].  See [`Cea608State::set_channel_inheritance`].
+    pub fn channel_inheritance(mut self, inheritance: ChannelInheritance) -> Self {
+        self.state.set_channel_inheritance(inheritance);
+        self
+    }
+
+    /// Construct the configured [`Cea608State`]
+    pub fn build(self) -> Cea608State {
+        self.state
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Code(Code),
+    Raw([u8; 2]),
+}
+
+/// A writer that handles combining single byte [`Code`]s and double byte [`Code`]s.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cea608Writer {
+    pending: VecDeque<Pending>,
+    pending_code: Option<Code>,
+    attributes: Option<(tables::Color, bool, bool)>,
+}
+
+impl Cea608Writer {
+    /// Push a [`Code`] into this writer
+    pub fn push(&mut self, code: Code) {
+        self.pending.push_front(Pending::Code(code))
+    }
+
+    /// Push a byte pair into this writer that will be produced unchanged by [`pop`](Self::pop).
+    ///
+    /// Unlike [`push`](Self::push), the parity of `data` is not corrected, which allows passing
+    /// through captured data including any parity errors, e.g. for forensic purposes.  The byte
+    /// pair is never combined with other [`Code`]s and no backspace handling is applied across
+    /// it.  As the data is not validated, this can produce byte pairs that decoders reject or
+    /// interpret differently than expected, e.g. an undoubled control code.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608Writer, tables::Code};
+    /// let mut writer = Cea608Writer::default();
+    /// writer.push(Code::LatinLowerA);
+    /// // 'A' with invalid parity
+    /// writer.push_raw([0x41, 0x80]);
+    /// assert_eq!(writer.pop(), [0x61, 0x80]);
+    /// assert_eq!(writer.pop(), [0x41, 0x80]);
+    /// ```
+    pub fn push_raw(&mut self, data: [u8; 2]) {
+        self.pending.push_front(Pending::Raw(data))
+    }
+
+    /// Push the original byte pair of a [`Token`] into this writer.  See
+    /// [`push_raw`](Self::push_raw) and [`Cea608State::decode_token`].
+    pub fn push_token(&mut self, token: &Token) {
+        self.push_raw(token.data())
+    }
+
+    /// Push the [`Code`] for a character into this writer
+    ///
+    /// Basic characters are written as a single byte.  Special and extended characters are
+    /// written as a control code on the provided [`Channel`].  For extended characters, a
+    /// replacement character that is removed by the extended character is written first (see
+    /// [`Code::needs_backspace`]).
+    ///
+    /// Returns [`WriterError::UnrepresentableChar`] if the character cannot be represented.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608Writer, tables::Channel};
+    /// let mut writer = Cea608Writer::default();
+    /// writer.push_char('A', Channel::ONE).unwrap();
+    /// assert_eq!(writer.pop(), [0xc1, 0x80]);
+    /// ```
+    pub fn push_char(&mut self, c: char, channel: Channel) -> Result<(), WriterError> {
+        let code = Code::from_char(c, channel).ok_or(WriterError::UnrepresentableChar(c))?;
+        self.push(code);
+        Ok(())
+    }
+
+    /// Push runs of text, each preceded by the mid-row control codes selecting its
+    /// [`StyleAttrs`].
+    ///
+    /// A mid-row code selecting italics keeps the previous color, so italics in a color other than
+    /// white needs a mid-row code for the color followed by a mid-row code for italics.  Each
+    /// mid-row code is displayed as a space.  As with [`push`](Self::push), control codes are not
+    /// doubled.
+    ///
+    /// Returns [`WriterError::UnrepresentableChar`] without pushing anything if any character
+    /// cannot be represented.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608Writer, StyleAttrs, tables::{Channel, Color, Field}};
+    /// let mut writer = Cea608Writer::default();
+    /// let italics = StyleAttrs { italics: true, ..StyleAttrs::default() };
+    /// writer.push_styled(Field::ONE, Channel::ONE, &[(italics, "Hi")]).unwrap();
+    /// assert_eq!(writer.drain_all(), [[0x91, 0xae], [0xc8, 0xe9]]);
+    /// ```
+    pub fn push_styled(
+        &mut self,
+        field: Field,
+        channel: Channel,
+        runs: &[(StyleAttrs, &str)],
+    ) -> Result<(), WriterError> {
+        let mut codes = vec![];
+        for (attrs, text) in runs {
+            let mut midrows = vec![];
+            if !attrs.italics || attrs.color != tables::Color::White {
+                midrows.push(MidRow::new_color(attrs.color, attrs.underline));
+            }
+            if attrs.italics {
+                midrows.push(MidRow::new_italics(attrs.underline));
+            }
+            codes.extend(midrows.into_iter().map(|midrow| {
+                Code::Control(tables::ControlCode::new(
+                    field,
+                    channel,
+                    tables::Control::MidRow(midrow),
+                ))
+            }));
+            for c in text.chars() {
+                codes.push(Code::from_char(c, channel).ok_or(WriterError::UnrepresentableChar(c))?);
+            }
+        }
+        for code in codes {
+            self.push(code);
+        }
+        Ok(())
+    }
+
+    /// Push the [`Code`]s needed to clear the displayed captions on the provided [`Field`] and
+    /// [`Channel`].
+    ///
+    /// This pushes an [`EraseDisplayedMemory`](tables::Control::EraseDisplayedMemory) control code
+    /// and, if `erase_non_displayed` is set, an
+    /// [`EraseNonDisplayedMemory`](tables::Control::EraseNonDisplayedMemory) control code.  As with
+    /// [`push`](Self::push), control codes are not doubled.
+    pub fn push_clear(&mut self, field: Field, channel: Channel, erase_non_displayed: bool) {
+        self.push(Code::Control(tables::ControlCode::new(
+            field,
+            channel,
+            tables::Control::EraseDisplayedMemory,
+        )));
+        if erase_non_displayed {
+            self.push(Code::Control(tables::ControlCode::new(
+                field,
+                channel,
+                tables::Control::EraseNonDisplayedMemory,
+            )));
+        }
+    }
+
+    /// Push a [`DeleteToEndOfRow`](tables::Control::DeleteToEndOfRow) control code for the provided
+    /// [`Field`] and [`Channel`].  As with [`push`](Self::push), the control code is not doubled.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608Writer, tables::{Channel, Field}};
+    /// let mut writer = Cea608Writer::default();
+    /// writer.push_delete_to_end_of_row(Field::ONE, Channel::ONE);
+    /// assert_eq!(writer.pop(), [0x94, 0xa4]);
+    /// ```
+    pub fn push_delete_to_end_of_row(&mut self, field: Field, channel: Channel) {
+        self.push(Code::Control(tables::ControlCode::new(
+            field,
+            channel,
+            tables::Control::DeleteToEndOfRow,
+        )));
+    }
+
+    /// Push the [`Code`]s needed to fully reset the provided [`Field`] and [`Channel`] into
+    /// `mode`.
+    ///
+    /// The following control codes are pushed in order:
+    /// 1. The control code selecting `mode`, e.g.
+    ///    [`ResumeCaptionLoading`](tables::Control::ResumeCaptionLoading) for [`Mode::PopOn`].
+    /// 2. [`EraseDisplayedMemory`](tables::Control::EraseDisplayedMemory)
+    /// 3. [`EraseNonDisplayedMemory`](tables::Control::EraseNonDisplayedMemory)
+    /// 4. The home [`PreambleAddress`](tables::Control::PreambleAddress), see
+    ///    [`PreambleAddressCode::home`].
+    ///
+    /// As with [`push`](Self::push), control codes are not doubled.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608Writer, Mode, tables::{Channel, Field}};
+    /// let mut writer = Cea608Writer::default();
+    /// writer.push_reset(Field::ONE, Channel::ONE, Mode::PopOn);
+    /// assert_eq!(
+    ///     writer.drain_all(),
+    ///     [[0x94, 0x20], [0x94, 0x2c], [0x94, 0xae], [0x94, 0x70]]
+    /// );
+    /// ```
+    pub fn push_reset(&mut self, field: Field, channel: Channel, mode: Mode) {
+        self.push(Code::Control(tables::ControlCode::new(
+            field,
+            channel,
+            mode.to_control(),
+        )));
+        self.push_clear(field, channel, true);
+        self.push(Code::Control(tables::ControlCode::new(
+            field,
+            channel,
+            tables::Control::PreambleAddress(PreambleAddressCode::home()),
+        )));
+    }
+
+    /// Pop a [`Code`] from this writer
+    pub fn pop(&mut self) -> [u8; 2] {
+        let mut ret = [0x80; 2];
+        let mut prev = None::<Code>;
+
+        if let Some(code) = self.pending_code.take() {
+            self.track_attributes(code);
+            code.write_into(&mut ret);
+            return ret;
+        }
+
+        while let Some(item) = self.pending.pop_back() {
+            let code = match item {
+                Pending::Code(code) => code,
+                Pending::Raw(data) => {
+                    if prev.is_some() {
+                        self.pending.push_back(item);
+                        break;
+                    }
+                    return data;
+                }
+            };
+            if let Some(prev) = prev {
+                if code.byte_len() == 1 {
+                    let mut data = [0; 2];
+                    prev.write_into(&mut ret);
+                    code.write_into(&mut data);
+                    ret[1] = data[0];
+                    return ret;
+                } else if code.needs_backspace() {
+                    self.pending_code = Some(code);
+                    let mut data = [0; 2];
+                    prev.write_into(&mut ret);
+                    Code::Space.write_into(&mut data);
+                    ret[1] = data[0];
+                    return ret;
+                } else {
+                    self.pending_code = Some(code);
+                    prev.write_into(&mut ret);
+                    return ret;
+                }
+            } else if code.needs_backspace() {
+                // all back space needing codes are 2 byte commands
                 self.pending_code = Some(code);
                 Code::Space.write_into(&mut ret);
                 return ret;
             } else if code.byte_len() == 1 {
                 prev = Some(code);
             } else {
+                self.track_attributes(code);
                 code.write_into(&mut ret);
                 return ret;
             }
@@ -320,11 +2007,118 @@ impl Cea608Writer {
         ret
     }
 
-    /// The number of codes currently stored
+    /// The color, underline and italics attributes set by the last preamble or mid-row control
+    /// code produced by [`pop`](Self::pop).
+    ///
+    /// Before any such control code has been produced, or after [`reset`](Self::reset), the
+    /// [default attributes](PreambleAddressCode::default_attributes) are returned.  A mid-row
+    /// code selecting italics keeps the previous color.
+    pub fn current_attributes(&self) -> (tables::Color, bool, bool) {
+        self.attributes
+            .unwrap_or_else(PreambleAddressCode::default_attributes)
+    }
+
+    fn track_attributes(&mut self, code: Code) {
+        let Code::Control(control_code) = code else {
+            return;
+        };
+        let (color, _, _) = self.current_attributes();
+        match control_code.code() {
+            tables::Control::PreambleAddress(preamble) => {
+                self.attributes =
+                    Some((preamble.color(), preamble.underline(), preamble.italics()));
+            }
+            tables::Control::MidRow(midrow) => {
+                self.attributes = Some(match midrow.color() {
+                    Some(color) => (color, midrow.underline(), false),
+                    None => (color, midrow.underline(), true),
+                });
+            }
+            _ => (),
+        }
+    }
+
+    /// Move all the pending [`Code`]s from `other` to the end of this writer.
+    ///
+    /// After this call, [`pop`](Self::pop) will first produce all of the data previously stored in
+    /// this writer, followed by all of the data that was stored in `other` in the same order it
+    /// would have been produced by `other`.  `other` will be empty afterwards.
+    ///
+    /// `other` may hold a look-ahead [`Code`] from a previous [`pop`](Self::pop), e.g. an
+    /// extended character whose replacement character was already produced.  If this writer is
+    /// empty, the look-ahead code is kept as is and is produced next without a second replacement
+    /// character.  Otherwise, it is requeued after the data of this writer and a new replacement
+    /// character is produced for it.
+    pub fn append(&mut self, other: &mut Cea608Writer) {
+        if let Some(code) = other.pending_code.take() {
+            if self.is_empty() {
+                self.pending_code = Some(code);
+            } else {
+                self.push(code);
+            }
+        }
+        while let Some(item) = other.pending.pop_back() {
+            self.pending.push_front(item);
+        }
+    }
+
+    /// Pop all of the stored [`Code`]s from this writer.
+    ///
+    /// The returned byte pairs do not include any trailing padding.
+    pub fn drain_all(&mut self) -> Vec<[u8; 2]> {
+        let mut ret = vec![];
+        while !self.is_empty() {
+            ret.push(self.pop());
+        }
+        ret
+    }
+
+    /// The number of frames needed to send all of the stored codes, assuming one byte pair is
+    /// popped per frame.
+    ///
+    /// Unlike [`n_codes`](Self::n_codes), this accounts for single byte codes being combined into
+    /// a single byte pair and for the replacement characters added before extended characters.
+    /// This is an estimate of the delay added by the queue: codes pushed later, padding inserted
+    /// by the caller or a lower rate of popping will increase the actual delay.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Cea608Writer, tables::Code};
+    /// let mut writer = Cea608Writer::default();
+    /// writer.push(Code::LatinLowerA);
+    /// writer.push(Code::LatinLowerB);
+    /// writer.push(Code::LatinLowerC);
+    /// assert_eq!(writer.n_codes(), 3);
+    /// assert_eq!(writer.queue_latency_frames(), 2);
+    /// ```
+    pub fn queue_latency_frames(&self) -> usize {
+        let mut writer = self.clone();
+        let mut n_frames = 0;
+        while !writer.is_empty() {
+            writer.pop();
+            n_frames += 1;
+        }
+        n_frames
+    }
+
+    /// The number of codes currently stored.  Byte pairs pushed with
+    /// [`push_raw`](Self::push_raw) count as a single code.
     pub fn n_codes(&self) -> usize {
         self.pending.len() + if self.pending_code.is_some() { 1 } else { 0 }
     }
 
+    /// Whether there are no codes currently stored
+    pub fn is_empty(&self) -> bool {
+        self.n_codes() == 0
+    }
+
+    /// The number of codes that can still be pushed before this writer holds `max` codes.
+    ///
+    /// Returns 0 if this writer already holds `max` or more codes.
+    pub fn remaining_capacity(&self, max: usize) -> usize {
+        max.saturating_sub(self.n_codes())
+    }
+
     /// Reset as if it was a newly created instance
     pub fn reset(&mut self) {
         *self = Self::default();
@@ -362,6 +2156,17 @@ impl Id {
         }
     }
 
+    /// Construct a [`ControlCode`](tables::ControlCode) for this [`Id`]'s [`Field`] and
+    /// [`Channel`]
+    pub fn control_code(&self, control: tables::Control) -> tables::ControlCode {
+        tables::ControlCode::new(self.field(), self.channel(), control)
+    }
+
+    /// Push a control code for this [`Id`]'s [`Field`] and [`Channel`] into a [`Cea608Writer`]
+    pub fn write_control(&self, control: tables::Control, writer: &mut Cea608Writer) {
+        writer.push(Code::Control(self.control_code(control)));
+    }
+
     /// Construct an [`Id`] from a [`Field`] and [`Channel`]
     pub fn from_caption_field_channel(field: Field, channel: Channel) -> Self {
         match (field, channel) {
@@ -373,13 +2178,22 @@ impl Id {
     }
 
     /// Construct an [`Id`] from its integer value in the range [1, 4]
-    pub fn from_value(value: i8) -> Self {
+    ///
+    /// Values outside of that range will return [`ParserError::InvalidId`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Id, ParserError};
+    /// assert_eq!(Id::from_value(3), Ok(Id::CC3));
+    /// assert_eq!(Id::from_value(5), Err(ParserError::InvalidId(5)));
+    /// ```
+    pub fn from_value(value: i8) -> Result<Self, ParserError> {
         match value {
-            1 => Self::CC1,
-            2 => Self::CC2,
-            3 => Self::CC3,
-            4 => Self::CC4,
-            _ => unreachable!(),
+            1 => Ok(Self::CC1),
+            2 => Ok(Self::CC2),
+            3 => Ok(Self::CC3),
+            4 => Ok(Self::CC4),
+            _ => Err(ParserError::InvalidId(value)),
         }
     }
 }
@@ -392,24 +2206,932 @@ mod test {
     use crate::tests::*;
 
     #[test]
-    fn state_duplicate_control() {
+    fn compact_keeps_data() {
+        test_init_log();
+        let pad = [0x80, 0x80];
+        assert_eq!(compact(&[], true), Vec::<[u8; 2]>::new());
+        assert_eq!(compact(&[pad, pad], true), Vec::<[u8; 2]>::new());
+        // RU2 RU2 pad pad 'A' pad CR pad pad pad CR 'B'
+        let pairs = [
+            [0x94, 0x25],
+            [0x94, 0x25],
+            pad,
+            pad,
+            [0xc1, 0x80],
+            pad,
+            [0x94, 0xad],
+            pad,
+            pad,
+            pad,
+            [0x94, 0xad],
+            [0xc2, 0x80],
+        ];
+        let compacted = compact(&pairs, true);
+        assert_eq!(
+            compacted,
+            [
+                [0x94, 0x25],
+                [0x94, 0x25],
+                [0xc1, 0x80],
+                [0x94, 0xad],
+                pad,
+                pad,
+                pad,
+                [0x94, 0xad],
+                [0xc2, 0x80],
+            ]
+        );
+        assert_eq!(compact(&pairs, false), pairs);
+        for window in [1, 2, 3, 4] {
+            let decode = |pairs: &[[u8; 2]]| {
+                let mut state = Cea608State::builder().dedup_window(window).build();
+                pairs
+                    .iter()
+                    .filter_map(|data| state.decode(*data).unwrap())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(decode(&pairs), decode(&compacted));
+        }
+    }
+
+    #[test]
+    fn detect_services_all() {
+        test_init_log();
+        let text = |channel| {
+            let mut writer = Cea608Writer::default();
+            let id = Id::from_caption_field_channel(Field::ONE, channel);
+            id.write_control(tables::Control::RollUp2, &mut writer);
+            writer.push_char('A', channel).unwrap();
+            writer.drain_all()
+        };
+        let field1 = [text(Channel::TWO), text(Channel::ONE)].concat();
+        assert_eq!(detect_services(&field1, &[]), [Id::CC1, Id::CC2]);
+        // field 2 miscellaneous control codes
+        let field2 = [[0x15, 0x25], [0xc1, 0x80], [0x9d, 0x25], [0xc1, 0x80]];
+        assert_eq!(
+            detect_services(&field1, &field2),
+            [Id::CC1, Id::CC2, Id::CC3, Id::CC4]
+        );
+        assert_eq!(detect_services(&[], &field2[..2]), [Id::CC3]);
+        assert_eq!(detect_services(&[[0x80, 0x80]], &[]), []);
+    }
+
+    #[test]
+    fn control_timeline_mixed() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        let events = codes![Field::ONE, Channel::ONE;
+            tables::Control::ResumeCaptionLoading,
+            tables::Control::PreambleAddress(PreambleAddressCode::home()),
+            'É',
+            'b',
+            tables::Control::EndOfCaption,
+        ]
+        .iter()
+        .map(|data| state.decode(*data).unwrap())
+        .collect::<Vec<_>>();
+        let text_indices = events
+            .iter()
+            .enumerate()
+            .filter(|(_, cea608)| matches!(cea608, Some(Cea608::Text(_))))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        assert!(!text_indices.is_empty());
+        let timeline = control_timeline(events.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            timeline
+                .iter()
+                .map(|(_, cea608)| *cea608)
+                .collect::<Vec<_>>(),
+            [
+                Cea608::NewMode(Channel::ONE, Mode::PopOn),
+                Cea608::Preamble(Channel::ONE, PreambleAddressCode::home()),
+                Cea608::EndOfCaption(Channel::ONE),
+            ]
+        );
+        for (idx, cea608) in timeline {
+            assert_eq!(events[idx], Some(cea608));
+            assert!(!text_indices.contains(&idx));
+        }
+    }
+
+    #[test]
+    fn merge_fields_order() {
+        test_init_log();
+        let field1 = [
+            (1, Cea608::EraseDisplay(Channel::ONE)),
+            (1, Cea608::EndOfCaption(Channel::TWO)),
+            (5, Cea608::CarriageReturn(Channel::ONE)),
+        ];
+        let field2 = [
+            (0, Cea608::EraseDisplay(Channel::TWO)),
+            (1, Cea608::Backspace(Channel::ONE)),
+            (7, Cea608::Backspace(Channel::ONE)),
+        ];
+        assert_eq!(
+            merge_fields(field1, field2).collect::<Vec<_>>(),
+            [
+                (0, Id::CC4, Cea608::EraseDisplay(Channel::TWO)),
+                (1, Id::CC1, Cea608::EraseDisplay(Channel::ONE)),
+                (1, Id::CC2, Cea608::EndOfCaption(Channel::TWO)),
+                (1, Id::CC3, Cea608::Backspace(Channel::ONE)),
+                (5, Id::CC1, Cea608::CarriageReturn(Channel::ONE)),
+                (7, Id::CC3, Cea608::Backspace(Channel::ONE)),
+            ]
+        );
+    }
+
+    #[test]
+    fn state_text_mode() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        // RU2, 'A'
+        assert_eq!(
+            Ok(Some(Cea608::NewMode(Channel::ONE, Mode::RollUp2))),
+            state.decode([0x94, 0x25])
+        );
+        assert!(matches!(
+            state.decode([0xc1, 0x80]),
+            Ok(Some(Cea608::Text(_)))
+        ));
+        let text_service = |data| Ok(Some(Cea608::TextService(Channel::ONE, data)));
+        // TR, 'B', CR, 'É'
+        assert_eq!(
+            text_service(TextServiceData::Control(tables::Control::TextRestart)),
+            state.decode([0x94, 0x2a])
+        );
+        assert_eq!(
+            text_service(TextServiceData::Text(Text {
+                needs_backspace: false,
+                char1: Some('B'),
+                char2: None,
+                channel: Channel::ONE,
+            })),
+            state.decode([0xc2, 0x80])
+        );
+        assert_eq!(
+            text_service(TextServiceData::Control(tables::Control::CarriageReturn)),
+            state.decode([0x94, 0xad])
+        );
+        assert_eq!(
+            text_service(TextServiceData::Text(Text {
+                needs_backspace: true,
+                char1: Some('É'),
+                char2: None,
+                channel: Channel::ONE,
+            })),
+            state.decode([0x92, 0xa1])
+        );
+        // the other channel is not affected
+        assert_eq!(
+            Ok(Some(Cea608::CarriageReturn(Channel::TWO))),
+            state.decode([0x1c, 0xad])
+        );
+        // RTD on channel 1, RCL on channel 2, 'A' on channel 2
+        assert_eq!(
+            text_service(TextServiceData::Control(tables::Control::ResumeTextDisplay)),
+            state.decode([0x94, 0xab])
+        );
+        assert!(matches!(
+            state.decode([0xc1, 0x80]),
+            Ok(Some(Cea608::TextService(
+                Channel::ONE,
+                TextServiceData::Text(_)
+            )))
+        ));
+        assert_eq!(
+            Ok(Some(Cea608::NewMode(Channel::TWO, Mode::PopOn))),
+            state.decode([0x1c, 0x20])
+        );
+        assert!(matches!(
+            state.decode([0xc1, 0x80]),
+            Ok(Some(Cea608::Text(_)))
+        ));
+        // RDC, 'A'
+        assert_eq!(
+            Ok(Some(Cea608::NewMode(Channel::ONE, Mode::PaintOn))),
+            state.decode([0x94, 0x29])
+        );
+        assert!(matches!(
+            state.decode([0xc1, 0x80]),
+            Ok(Some(Cea608::Text(_)))
+        ));
+    }
+
+    #[test]
+    fn state_text_without_mode() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        // PAC on channel 2 followed by 'AB'
+        assert!(matches!(
+            state.decode([0x1c, 0x70]),
+            Ok(Some(Cea608::Preamble(Channel::TWO, _)))
+        ));
+        assert!(matches!(
+            state.decode([0xc1, 0xc2]),
+            Ok(Some(Cea608::Text(_)))
+        ));
+        assert_eq!(state.mode(Channel::TWO), None);
+        assert_eq!(state.n_texts_without_mode(Channel::TWO), 1);
+        assert_eq!(state.n_texts_without_mode(Channel::ONE), 0);
+        // RU2 and 'A'
+        state.decode([0x1c, 0x25]).unwrap();
+        state.decode([0xc1, 0x80]).unwrap();
+        assert_eq!(state.mode(Channel::TWO), Some(Mode::RollUp2));
+        assert_eq!(state.n_texts_without_mode(Channel::TWO), 1);
+        assert_eq!(Cea608State::from_snapshot(state.state_snapshot()), state);
+        state.reset();
+        assert_eq!(state.mode(Channel::TWO), None);
+        assert_eq!(state.n_texts_without_mode(Channel::TWO), 0);
+    }
+
+    #[test]
+    fn state_raw_controls() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        // alarm on is dropped by default
+        assert_eq!(Ok(None), state.decode([0x94, 0x23]));
+        let mut state = Cea608State::builder().raw_controls(true).build();
+        let alarm_on = [0x94, 0x23];
+        let unknown = [0x97, 0xb0];
+        let mut writer = Cea608Writer::default();
+        for data in [alarm_on, unknown] {
+            let Ok(Some(cea608)) = state.decode(data) else {
+                unreachable!();
+            };
+            assert_eq!(cea608.category(), EventCategory::Other);
+            let Cea608::RawControl(channel, field, raw) = cea608 else {
+                unreachable!();
+            };
+            assert_eq!(channel, Channel::ONE);
+            assert_eq!(raw, data);
+            writer.push_raw(raw);
+            assert_eq!(writer.pop(), data);
+            if data == alarm_on {
+                assert_eq!(field, Some(Field::ONE));
+                assert_eq!(cea608.control(), Some(tables::Control::AlarmOn));
+            } else {
+                assert!(matches!(
+                    cea608.control(),
+                    Some(tables::Control::Unknown(_))
+                ));
+            }
+        }
+        state.reset();
+        assert!(state.raw_controls());
+    }
+
+    #[test]
+    fn state_builder() {
+        test_init_log();
+        assert_eq!(Cea608State::builder().build(), Cea608State::default());
+        let state = Cea608State::builder()
+            .dedup(false)
+            .dedup_window(3)
+            .auto_reset_on_field_change(true)
+            .initial_control_is_tail(true)
+            .transcript_enabled(true)
+            .build();
+        assert!(!state.dedup());
+        assert_eq!(state.dedup_window(), 3);
+        assert!(state.auto_reset_on_field_change());
+        assert!(state.initial_control_is_tail());
+        assert!(state.transcript_enabled());
+    }
+
+    #[test]
+    fn state_snapshot_roundtrip() {
+        test_init_log();
+        let mut state = Cea608State::builder()
+            .dedup_window(2)
+            .transcript_enabled(true)
+            .build();
+        // RU2 on channel 2, 'A', EDM, padding
+        for data in [[0x1c, 0x25], [0xc1, 0x80], [0x1c, 0x2c], [0x80, 0x80]] {
+            state.decode(data).unwrap();
+        }
+        let snapshot = state.state_snapshot();
+        assert_eq!(snapshot.last_data, [Some([0x1c, 0x2c]), None]);
+        assert_eq!(snapshot.last_channel, Some(Channel::TWO));
+        assert_eq!(snapshot.last_received_field, Some(Field::ONE));
+        assert_eq!(snapshot.n_padding, 1);
+        let mut restored = Cea608State::from_snapshot(snapshot.clone());
+        assert_eq!(restored, state);
+        assert_eq!(restored.state_snapshot(), snapshot);
+        // the duplicate EDM after padding is still skipped
+        assert_eq!(Ok(None), restored.decode([0x1c, 0x2c]));
+        assert_eq!(restored.transcript(Channel::TWO), "A");
+    }
+
+    #[test]
+    fn state_token_roundtrip() {
+        test_init_log();
+        let pairs = [
+            [0x80, 0x80],
+            // RU2 RU2 pad
+            [0x94, 0x25],
+            [0x94, 0x25],
+            [0x80, 0x80],
+            // 'A' 'B', invalid parity, 'É' on channel 1
+            [0xc1, 0xc2],
+            [0x41, 0x80],
+            [0xc1, 0x80],
+            [0x92, 0xa1],
+            [0x92, 0xa1],
+            [0x80, 0x80],
+            [0x80, 0x80],
+        ];
+        let mut state = Cea608State::default();
+        let tokens = pairs
+            .iter()
+            .map(|data| state.decode_token(*data))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens[2], Token::Skipped([0x94, 0x25]));
+        assert_eq!(
+            tokens[5],
+            Token::Invalid {
+                data: [0x41, 0x80],
+                error: ParserError::InvalidParity
+            }
+        );
+        assert_eq!(tokens.iter().filter_map(Token::event).count(), 4);
+        let mut writer = Cea608Writer::default();
+        for token in &tokens {
+            writer.push_token(token);
+        }
+        assert_eq!(writer.drain_all(), pairs);
+    }
+
+    #[test]
+    fn state_decode_with_channel() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        let text = |c, channel| {
+            Cea608::Text(Text {
+                needs_backspace: false,
+                char1: Some(c),
+                char2: None,
+                channel,
+            })
+        };
+        assert_eq!(
+            state.decode_with_channel([0xc1, 0x80], Channel::TWO),
+            Ok(Some(text('A', Channel::TWO)))
+        );
+        // the fallback is not stored
+        assert_eq!(state.decode([0xc2, 0x80]), Ok(None));
+        // RU2 on channel 1 takes precedence over the fallback
+        assert_eq!(
+            state.decode_with_channel([0x94, 0x25], Channel::TWO),
+            Ok(Some(Cea608::NewMode(Channel::ONE, Mode::RollUp2)))
+        );
+        assert_eq!(
+            state.decode_with_channel([0xc2, 0x80], Channel::TWO),
+            Ok(Some(text('B', Channel::ONE)))
+        );
+    }
+
+    #[test]
+    fn state_duplicate_control() {
+        test_init_log();
+        let data = codes![Field::ONE, Channel::ONE; tables::Control::EraseDisplayedMemory];
+        let mut state = Cea608State::default();
+        assert_eq!(
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
+            state.decode(data[0])
+        );
+        assert_eq!(state.last_received_field(), Some(Field::ONE));
+        assert_eq!(Ok(None), state.decode(data[0]));
+        assert_eq!(state.last_received_field(), Some(Field::ONE));
+    }
+
+    #[test]
+    fn state_duplicate_control_per_field() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        // EDM and EOC interleaved on field 1 and field 2 with each control doubled
+        let field1 = codes![
+            Field::ONE, Channel::ONE;
+            tables::Control::EraseDisplayedMemory,
+            tables::Control::EndOfCaption,
+        ];
+        let field2 = codes![
+            Field::TWO, Channel::ONE;
+            tables::Control::EraseDisplayedMemory,
+            tables::Control::EndOfCaption,
+        ];
+        let mut events = vec![];
+        for (f1, f2) in field1.iter().zip(field2.iter()) {
+            for _ in 0..2 {
+                events.push(state.decode(*f1).unwrap());
+                events.push(state.decode(*f2).unwrap());
+            }
+        }
+        let edm = Some(Cea608::EraseDisplay(Channel::ONE));
+        let eoc = Some(Cea608::EndOfCaption(Channel::ONE));
+        assert_eq!(events, [edm, edm, None, None, eoc, eoc, None, None]);
+    }
+
+    #[test]
+    fn state_drop_callback() {
+        test_init_log();
+        let dropped = Arc::new(Mutex::new(vec![]));
+        let mut state = Cea608State::default();
+        let dropped_clone = dropped.clone();
+        state.set_drop_callback(move |reason, data| {
+            dropped_clone.lock().unwrap().push((reason, data))
+        });
+        // 'A' without channel, EDM twice, padding and alarm off
+        for data in [
+            [0xc1, 0x80],
+            [0x94, 0x2c],
+            [0x94, 0x2c],
+            [0x80, 0x80],
+            [0x94, 0xa2],
+        ] {
+            let _ = state.decode(data).unwrap();
+        }
+        // TR and 'A' are passed through as Text service data
+        assert!(state.decode([0x94, 0x2a]).unwrap().is_some());
+        assert_eq!(
+            state.decode([0xc1, 0x80]),
+            Ok(Some(Cea608::TextService(
+                Channel::ONE,
+                TextServiceData::Text(Text {
+                    needs_backspace: false,
+                    char1: Some('A'),
+                    char2: None,
+                    channel: Channel::ONE,
+                })
+            )))
+        );
+        assert_eq!(
+            *dropped.lock().unwrap(),
+            [
+                (DropReason::NoChannel, [0xc1, 0x80]),
+                (DropReason::Duplicate, [0x94, 0x2c]),
+                (DropReason::Padding, [0x80, 0x80]),
+                (DropReason::Unknown, [0x94, 0xa2]),
+            ]
+        );
+        let cloned = state.clone();
+        assert_eq!(cloned, state);
+        state.reset();
+        state.clear_drop_callback();
+        state.decode([0x80, 0x80]).unwrap();
+        assert_eq!(dropped.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn state_channel_inheritance() {
+        test_init_log();
+        let text = |channel| {
+            Some(Cea608::Text(Text {
+                needs_backspace: false,
+                char1: Some('A'),
+                char2: None,
+                channel,
+            }))
+        };
+        // EDM on CC2, 'A', padding, padding, 'A'
+        let pairs = [
+            [0x1c, 0x2c],
+            [0xc1, 0x80],
+            [0x80, 0x80],
+            [0x80, 0x80],
+            [0xc1, 0x80],
+        ];
+        let decode = |inheritance| {
+            let mut state = Cea608State::builder()
+                .channel_inheritance(inheritance)
+                .build();
+            pairs
+                .iter()
+                .map(|data| state.decode(*data).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let edm = Some(Cea608::EraseDisplay(Channel::TWO));
+
+        assert_eq!(
+            Cea608State::default().channel_inheritance(),
+            ChannelInheritance::Always
+        );
+        assert_eq!(
+            decode(ChannelInheritance::Always),
+            [edm, text(Channel::TWO), None, None, text(Channel::TWO)]
+        );
+        assert_eq!(
+            decode(ChannelInheritance::WithinWindow(3)),
+            [edm, text(Channel::TWO), None, None, None]
+        );
+        assert_eq!(
+            decode(ChannelInheritance::WithinWindow(4)),
+            decode(ChannelInheritance::Always)
+        );
+        assert_eq!(
+            decode(ChannelInheritance::Never),
+            [edm, None, None, None, None]
+        );
+
+        // the second copy of a doubled control code does not count
+        let mut state = Cea608State::builder()
+            .channel_inheritance(ChannelInheritance::WithinWindow(1))
+            .build();
+        assert_eq!(state.decode([0x1c, 0x2c]), Ok(edm));
+        assert_eq!(state.decode([0x1c, 0x2c]), Ok(None));
+        assert_eq!(state.decode([0xc1, 0x80]), Ok(text(Channel::TWO)));
+        assert_eq!(state.decode([0x80, 0x80]), Ok(None));
+        assert_eq!(state.decode([0xc1, 0x80]), Ok(None));
+
+        // a fallback channel is still used when the channel is not inherited
+        let mut state = Cea608State::builder()
+            .channel_inheritance(ChannelInheritance::Never)
+            .build();
+        state.decode([0x1c, 0x2c]).unwrap();
+        assert_eq!(
+            state.decode_with_channel([0xc1, 0x80], Channel::ONE),
+            Ok(text(Channel::ONE))
+        );
+        state.reset();
+        assert_eq!(state.channel_inheritance(), ChannelInheritance::Never);
+    }
+
+    #[test]
+    fn state_decode_with_field() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        // RU2 on CC3, 'A', EDM on CC2
+        assert_eq!(
+            state.decode_with_field([0x15, 0x25]),
+            Ok(Some((
+                Cea608::NewMode(Channel::ONE, Mode::RollUp2),
+                Some(Field::TWO)
+            )))
+        );
+        assert_eq!(
+            state.decode_with_field([0xc1, 0x80]),
+            Ok(Some((
+                Cea608::Text(Text {
+                    needs_backspace: false,
+                    char1: Some('A'),
+                    char2: None,
+                    channel: Channel::ONE,
+                }),
+                None
+            )))
+        );
+        assert_eq!(
+            state.decode_with_field([0x1c, 0x2c]),
+            Ok(Some((Cea608::EraseDisplay(Channel::TWO), Some(Field::ONE))))
+        );
+        // preambles do not signal a field
+        assert_eq!(
+            state.decode_with_field([0x94, 0x70]),
+            Ok(Some((
+                Cea608::Preamble(Channel::ONE, PreambleAddressCode::home()),
+                None
+            )))
+        );
+    }
+
+    #[test]
+    fn state_reset_first_control() {
+        test_init_log();
+        let edm = Some(Cea608::EraseDisplay(Channel::ONE));
+        let mut state = Cea608State::default();
+        assert_eq!(state.decode([0x94, 0x2c]), Ok(edm));
+        state.reset();
+        assert_eq!(state.decode([0x94, 0x2c]), Ok(edm));
+        assert_eq!(state.decode([0x94, 0x2c]), Ok(None));
+
+        state.reset_with_last_data([0x94, 0x2c]);
+        assert_eq!(state.last_data(), Some([0x94, 0x2c]));
+        assert_eq!(state.last_channel, None);
+        assert_eq!(state.decode([0x94, 0x2c]), Ok(None));
+        assert!(state.decode([0xc1, 0x80]).unwrap().is_none());
+        assert_eq!(state.decode([0x94, 0x2c]), Ok(edm));
+
+        // field 2
+        state.reset_with_last_data([0x15, 0x2c]);
+        assert_eq!(state.decode([0x94, 0x2c]), Ok(edm));
+        assert_eq!(state.decode([0x15, 0x2c]), Ok(None));
+
+        // a different byte pair is not a duplicate
+        state.reset_with_last_data([0x94, 0x2c]);
+        assert_eq!(
+            state.decode([0x94, 0x2f]),
+            Ok(Some(Cea608::EndOfCaption(Channel::ONE)))
+        );
+    }
+
+    #[test]
+    fn state_stats() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        // stuck 'AB' after RU2, interrupted by padding
+        state.decode([0x94, 0x25]).unwrap();
+        for _ in 0..5 {
+            state.decode([0xc1, 0xc2]).unwrap();
+        }
+        state.decode([0x80, 0x80]).unwrap();
+        state.decode([0xc1, 0xc2]).unwrap();
+        assert_eq!(state.stats().max_repeat_run(), 5);
+        assert_eq!(state.stats().n_events(), 7);
+        assert_eq!(state.stats().n_mode_commands(Mode::RollUp2), 1);
+        state.reset();
+        assert_eq!(*state.stats(), stats::Stats::default());
+    }
+
+    #[test]
+    fn state_set_last_data() {
+        test_init_log();
+        let data = codes![Field::ONE, Channel::ONE; tables::Control::EraseDisplayedMemory][0];
+        let mut state = Cea608State::default();
+        assert_eq!(state.last_data(), None);
+        state.set_last_data(Some(data));
+        assert_eq!(state.last_data(), Some(data));
+        assert_eq!(Ok(None), state.decode(data));
+        state.set_last_data(None);
+        assert_eq!(
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
+            state.decode(data)
+        );
+    }
+
+    #[test]
+    fn codes_macro() {
+        test_init_log();
+        let data = codes![
+            Field::TWO, Channel::TWO;
+            tables::Control::ResumeCaptionLoading,
+            "AB",
+            'É',
+            tables::Control::EndOfCaption,
+        ];
+        let mut state = Cea608State::default();
+        let events = data
+            .iter()
+            .filter_map(|data| state.decode(*data).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], Cea608::NewMode(Channel::TWO, Mode::PopOn));
+        assert_eq!(events[4], Cea608::EndOfCaption(Channel::TWO));
+        assert_eq!(state.last_received_field(), Some(Field::TWO));
+    }
+
+    #[test]
+    fn state_duplicate_control_after_padding() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        assert_eq!(state.dedup_window(), 1);
+        assert_eq!(
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
+            state.decode([0x94, 0x2c])
+        );
+        assert_eq!(Ok(None), state.decode([0x80, 0x80]));
+        assert_eq!(
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
+            state.decode([0x94, 0x2c])
+        );
+
+        state.reset();
+        state.set_dedup_window(2);
+        assert_eq!(
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
+            state.decode([0x94, 0x2c])
+        );
+        assert_eq!(Ok(None), state.decode([0x80, 0x80]));
+        assert_eq!(Ok(None), state.decode([0x94, 0x2c]));
+
+        // two padding pairs is outside of the window
+        assert_eq!(Ok(None), state.decode([0x80, 0x80]));
+        assert_eq!(Ok(None), state.decode([0x80, 0x80]));
+        assert_eq!(
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
+            state.decode([0x94, 0x2c])
+        );
+
+        state.reset();
+        assert_eq!(state.dedup_window(), 2);
+    }
+
+    #[test]
+    fn state_no_dedup() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        assert!(state.dedup());
+        state.set_dedup(false);
+        assert!(!state.dedup());
+        // two consecutive backspaces are two separate commands
+        for _ in 0..2 {
+            assert_eq!(
+                Ok(Some(Cea608::Backspace(Channel::ONE))),
+                state.decode([0x94, 0xa1])
+            );
+        }
+        state.reset();
+        assert!(!state.dedup());
+        state.set_dedup(true);
+        assert_eq!(
+            Ok(Some(Cea608::Backspace(Channel::ONE))),
+            state.decode([0x94, 0xa1])
+        );
+        assert_eq!(Ok(None), state.decode([0x94, 0xa1]));
+    }
+
+    #[test]
+    fn state_auto_reset_on_field_change() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        state.set_auto_reset_on_field_change(true);
+        assert!(state.auto_reset_on_field_change());
+        assert_eq!(
+            Ok(Some(Cea608::NewMode(Channel::ONE, Mode::RollUp2))),
+            state.decode([0x94, 0x25])
+        );
+        assert_eq!(state.last_received_field(), Some(Field::ONE));
+        // field 2 data received, previous state must be forgotten
+        assert_eq!(
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
+            state.decode([0x15, 0x2c])
+        );
+        assert_eq!(state.last_received_field(), Some(Field::TWO));
+        let mut fresh = Cea608State::default();
+        fresh.set_auto_reset_on_field_change(true);
+        fresh.decode([0x15, 0x2c]).unwrap();
+        assert_eq!(state.last_data, fresh.last_data);
+        assert_eq!(state.last_channel, fresh.last_channel);
+        assert_eq!(state.n_padding, fresh.n_padding);
+    }
+
+    #[test]
+    fn state_auto_reset_keeps_transcript_and_stats() {
+        test_init_log();
+        let mut state = Cea608State::builder()
+            .auto_reset_on_field_change(true)
+            .transcript_enabled(true)
+            .build();
+        // RU2 on CC1, "ab", RU2 on CC3, "cd"
+        for data in [[0x94, 0x25], [0x61, 0x62], [0x15, 0x25], [0xe3, 0x64]] {
+            state.decode(data).unwrap();
+        }
+        assert_eq!(state.last_received_field(), Some(Field::TWO));
+        assert_eq!(state.transcript(Channel::ONE), "abcd");
+        assert_eq!(state.stats().n_events(), 4);
+        assert_eq!(state.stats().n_mode_commands(Mode::RollUp2), 2);
+        assert_eq!(state.mode(Channel::ONE), Some(Mode::RollUp2));
+    }
+
+    #[test]
+    fn state_tab_offset() {
+        test_init_log();
+        for id in [Id::CC1, Id::CC2, Id::CC3, Id::CC4] {
+            for offset in 1..=3 {
+                let mut data = vec![];
+                Code::Control(ControlCode::new(
+                    id.field(),
+                    id.channel(),
+                    tables::Control::tab_offset(offset).unwrap(),
+                ))
+                .write(&mut data)
+                .unwrap();
+                let byte0 = if id.channel() == Channel::ONE {
+                    0x97
+                } else {
+                    0x1f
+                };
+                let byte1 = [0xa1, 0xa2, 0x23][offset as usize - 1];
+                assert_eq!(data, [byte0, byte1]);
+                let mut state = Cea608State::default();
+                assert_eq!(
+                    Ok(Some(Cea608::TabOffset(id.channel(), offset))),
+                    state.decode([data[0], data[1]])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn state_text_with_null() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        assert_eq!(
+            Ok(Some(Cea608::NewMode(Channel::ONE, Mode::RollUp2))),
+            state.decode([0x94, 0x25])
+        );
+        assert_eq!(
+            Ok(Some(Cea608::Text(Text {
+                needs_backspace: false,
+                char1: Some('A'),
+                char2: None,
+                channel: Channel::ONE,
+            }))),
+            state.decode([0xc1, 0x80])
+        );
+        assert_eq!(
+            Ok(Some(Cea608::Text(Text {
+                needs_backspace: false,
+                char1: None,
+                char2: Some('B'),
+                channel: Channel::ONE,
+            }))),
+            state.decode([0x80, 0xc2])
+        );
+    }
+
+    #[test]
+    fn state_initial_control_is_tail() {
         test_init_log();
-        let mut data = vec![];
-        Code::Control(ControlCode::new(
-            Field::ONE,
-            Channel::ONE,
-            tables::Control::EraseDisplayedMemory,
-        ))
-        .write(&mut data)
-        .unwrap();
+        // stream starts on the second copy of EDM followed by a doubled EDM
+        let stream = [[0x94, 0x2c], [0x94, 0x2c], [0x94, 0x2c]];
+
+        let mut state = Cea608State::default();
+        let events = stream
+            .iter()
+            .filter_map(|data| state.decode(*data).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events, [Cea608::EraseDisplay(Channel::ONE)]);
+
         let mut state = Cea608State::default();
+        state.set_initial_control_is_tail(true);
+        assert!(state.initial_control_is_tail());
+        let events = stream
+            .iter()
+            .filter_map(|data| state.decode(*data).unwrap())
+            .collect::<Vec<_>>();
         assert_eq!(
-            Ok(Some(Cea608::EraseDisplay(Channel::ONE))),
-            state.decode([data[0], data[1]])
+            events,
+            [
+                Cea608::EraseDisplay(Channel::ONE),
+                Cea608::EraseDisplay(Channel::ONE)
+            ]
         );
-        assert_eq!(state.last_received_field(), Some(Field::ONE));
-        assert_eq!(Ok(None), state.decode([data[0], data[1]]));
-        assert_eq!(state.last_received_field(), Some(Field::ONE));
+
+        // only the first control is affected
+        state.reset();
+        assert!(state.initial_control_is_tail());
+        assert_eq!(state.decode([0xc1, 0x80]), Ok(None));
+        assert_eq!(
+            state.decode([0x94, 0x2c]),
+            Ok(Some(Cea608::EraseDisplay(Channel::ONE)))
+        );
+        assert_eq!(state.decode([0x94, 0x2c]), Ok(None));
+    }
+
+    #[test]
+    fn state_transcript() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        assert!(!state.transcript_enabled());
+        state.set_transcript_enabled(true);
+        assert!(state.transcript_enabled());
+        for data in [
+            // RU2 CC1, "ab", RU2 CC2, "cd"
+            [0x94, 0x25],
+            [0x61, 0x62],
+            [0x1c, 0x25],
+            [0xe3, 0x64],
+            // CR CC1, "e ", Á
+            [0x94, 0xad],
+            [0xe5, 0x20],
+            [0x92, 0x20],
+        ] {
+            state.decode(data).unwrap();
+        }
+        assert_eq!(state.transcript(Channel::ONE), "ab\neÁ");
+        // BS
+        state.decode([0x94, 0xa1]).unwrap();
+        assert_eq!(state.transcript(Channel::ONE), "ab\ne");
+        assert_eq!(state.transcript(Channel::TWO), "cd");
+        state.reset();
+        assert_eq!(state.transcript(Channel::ONE), "");
+        for data in [
+            // RDC, PAC row 1 indent 4, "abcdef", PAC row 1 indent 4, TO2, DER
+            [0x94, 0x29],
+            [0x91, 0x52],
+            [0x61, 0x62],
+            [0xe3, 0x64],
+            [0xe5, 0xe6],
+            [0x91, 0x52],
+            [0x97, 0xa2],
+            [0x94, 0xa4],
+        ] {
+            state.decode(data).unwrap();
+        }
+        assert_eq!(state.transcript(Channel::ONE), "ab");
+        // "gh", DER at the end of the line
+        state.decode([0x67, 0x68]).unwrap();
+        state.decode([0x94, 0xa4]).unwrap();
+        assert_eq!(state.transcript(Channel::ONE), "abgh");
+        // CR, DER on an empty line
+        state.decode([0x94, 0xad]).unwrap();
+        state.decode([0x94, 0xa4]).unwrap();
+        assert_eq!(state.transcript(Channel::ONE), "abgh\n");
+        state.reset();
+        assert!(state.transcript_enabled());
+        assert_eq!(state.transcript(Channel::ONE), "");
+        state.set_transcript_enabled(false);
+        assert_eq!(state.transcript(Channel::ONE), "");
     }
 
     #[test]
@@ -471,6 +3193,271 @@ mod test {
         );
     }
 
+    #[test]
+    fn state_clone() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        assert_eq!(
+            Ok(Some(Cea608::NewMode(Channel::ONE, Mode::RollUp2))),
+            state.decode([0x94, 0x25])
+        );
+        let mut snapshot = state.clone();
+        assert_eq!(state, snapshot);
+        assert_eq!(Ok(None), state.decode([0x94, 0x25]));
+        assert_eq!(Ok(None), snapshot.decode([0x94, 0x25]));
+        assert_eq!(state, snapshot);
+        state.reset();
+        assert_ne!(state, snapshot);
+    }
+
+    #[test]
+    fn id_from_value() {
+        test_init_log();
+        assert_eq!(Id::from_value(1), Ok(Id::CC1));
+        assert_eq!(Id::from_value(2), Ok(Id::CC2));
+        assert_eq!(Id::from_value(3), Ok(Id::CC3));
+        assert_eq!(Id::from_value(4), Ok(Id::CC4));
+        assert_eq!(Id::from_value(0), Err(ParserError::InvalidId(0)));
+        assert_eq!(Id::from_value(5), Err(ParserError::InvalidId(5)));
+    }
+
+    #[test]
+    fn text_is_extended() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        // music note
+        let Ok(Some(Cea608::Text(text))) = state.decode([0x91, 0x37]) else {
+            unreachable!();
+        };
+        assert_eq!(text.char1, Some('♪'));
+        assert!(!text.needs_backspace);
+        assert!(!text.is_extended());
+        // É
+        let Ok(Some(Cea608::Text(text))) = state.decode([0x92, 0xa1]) else {
+            unreachable!();
+        };
+        assert_eq!(text.char1, Some('É'));
+        assert!(text.needs_backspace);
+        assert!(text.is_extended());
+        // basic
+        let Ok(Some(Cea608::Text(text))) = state.decode([0xc1, 0x80]) else {
+            unreachable!();
+        };
+        assert!(!text.is_extended());
+    }
+
+    #[test]
+    fn cea608_columns_advanced() {
+        test_init_log();
+        let text = Text {
+            needs_backspace: false,
+            char1: Some('a'),
+            char2: Some('b'),
+            channel: Channel::ONE,
+        };
+        assert_eq!(Cea608::Text(text).columns_advanced(), 2);
+        let text = Text {
+            needs_backspace: true,
+            char1: Some('é'),
+            char2: None,
+            channel: Channel::ONE,
+        };
+        assert_eq!(Cea608::Text(text).columns_advanced(), 0);
+        assert!(!Cea608::Text(text).advances_cursor());
+        let midrow = Cea608::MidRowChange(Channel::ONE, MidRow::new_italics(false));
+        assert_eq!(midrow.columns_advanced(), 1);
+        assert_eq!(Cea608::TabOffset(Channel::ONE, 3).columns_advanced(), 3);
+        assert!(!Cea608::CarriageReturn(Channel::ONE).advances_cursor());
+    }
+
+    #[test]
+    fn cea608_row() {
+        test_init_log();
+        let preamble = PreambleAddressCode::new(11, false, tables::PreambleType::Indent8);
+        assert_eq!(Cea608::Preamble(Channel::ONE, preamble).row(), Some(11));
+        assert_eq!(Cea608::CarriageReturn(Channel::ONE).row(), None);
+    }
+
+    #[test]
+    fn mode_to_control() {
+        test_init_log();
+        for mode in [
+            Mode::PopOn,
+            Mode::PaintOn,
+            Mode::RollUp2,
+            Mode::RollUp3,
+            Mode::RollUp4,
+        ] {
+            assert_eq!(mode.to_control().to_mode(), Some(mode));
+        }
+        assert_eq!(
+            Mode::PaintOn.to_control(),
+            tables::Control::ResumeDirectionCaptioning
+        );
+    }
+
+    #[test]
+    fn mode_row_semantics() {
+        test_init_log();
+        for (mode, absolute, scrolls) in [
+            (Mode::PopOn, true, false),
+            (Mode::PaintOn, true, false),
+            (Mode::RollUp2, false, true),
+            (Mode::RollUp3, false, true),
+            (Mode::RollUp4, false, true),
+        ] {
+            assert_eq!(mode.is_absolute_positioning(), absolute);
+            assert_eq!(mode.scrolls(), scrolls);
+        }
+    }
+
+    #[test]
+    fn mode_transition() {
+        test_init_log();
+        assert_eq!(
+            Mode::RollUp2.transition(Mode::RollUp4),
+            ModeTransition::ResizeWindow
+        );
+        assert_eq!(
+            Mode::RollUp4.transition(Mode::RollUp3),
+            ModeTransition::ResizeWindow
+        );
+        assert_eq!(
+            Mode::RollUp3.transition(Mode::RollUp3),
+            ModeTransition::PreserveRows
+        );
+        assert_eq!(
+            Mode::RollUp3.transition(Mode::PopOn),
+            ModeTransition::ClearScreen
+        );
+        assert_eq!(
+            Mode::PaintOn.transition(Mode::RollUp2),
+            ModeTransition::ClearScreen
+        );
+        assert_eq!(
+            Mode::PaintOn.transition(Mode::PopOn),
+            ModeTransition::PreserveRows
+        );
+    }
+
+    #[test]
+    fn cea608_category() {
+        test_init_log();
+        let preamble = PreambleAddressCode::new(11, false, tables::PreambleType::Indent8);
+        let midrow = MidRow::new_italics(false);
+        for (cea608, category) in [
+            (
+                Cea608::Preamble(Channel::ONE, preamble),
+                EventCategory::Positioning,
+            ),
+            (
+                Cea608::MidRowChange(Channel::ONE, midrow),
+                EventCategory::Styling,
+            ),
+            (Cea608::EraseNonDisplay(Channel::TWO), EventCategory::Erase),
+            (
+                Cea608::NewMode(Channel::ONE, Mode::PopOn),
+                EventCategory::ModeChange,
+            ),
+            (
+                Cea608::EndOfCaption(Channel::ONE),
+                EventCategory::ModeChange,
+            ),
+            (
+                Cea608::DeleteToEndOfRow(Channel::ONE),
+                EventCategory::Cursor,
+            ),
+            (Cea608::TabOffset(Channel::ONE, 2), EventCategory::Cursor),
+        ] {
+            assert_eq!(cea608.category(), category);
+            assert_eq!(cea608.is_erase(), category == EventCategory::Erase);
+        }
+        assert!(Cea608::EraseDisplay(Channel::ONE).is_erase());
+    }
+
+    #[test]
+    fn cea608_control_roundtrip() {
+        test_init_log();
+        let preamble = PreambleAddressCode::new(11, true, tables::PreambleType::Indent8);
+        let events = [
+            Cea608::NewMode(Channel::TWO, Mode::PopOn),
+            Cea608::NewMode(Channel::TWO, Mode::PaintOn),
+            Cea608::NewMode(Channel::TWO, Mode::RollUp2),
+            Cea608::NewMode(Channel::TWO, Mode::RollUp3),
+            Cea608::NewMode(Channel::TWO, Mode::RollUp4),
+            Cea608::EraseDisplay(Channel::TWO),
+            Cea608::EraseNonDisplay(Channel::TWO),
+            Cea608::CarriageReturn(Channel::TWO),
+            Cea608::Backspace(Channel::TWO),
+            Cea608::EndOfCaption(Channel::TWO),
+            Cea608::TabOffset(Channel::TWO, 2),
+            Cea608::DeleteToEndOfRow(Channel::TWO),
+            Cea608::Preamble(Channel::TWO, preamble),
+            Cea608::MidRowChange(Channel::TWO, MidRow::new_italics(true)),
+        ];
+        for event in events {
+            let control = event.control().unwrap();
+            let mut state = Cea608State::default();
+            let data = Code::Control(Id::CC2.control_code(control)).into();
+            assert_eq!(state.decode(data), Ok(Some(event)));
+        }
+        assert_eq!(Cea608::TabOffset(Channel::ONE, 4).control(), None);
+        let text = Text {
+            needs_backspace: false,
+            char1: Some('a'),
+            char2: None,
+            channel: Channel::ONE,
+        };
+        assert_eq!(Cea608::Text(text).control(), None);
+    }
+
+    #[test]
+    fn events_full_clear() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        let mut writer = Cea608Writer::default();
+        writer.push_clear(Field::ONE, Channel::TWO, true);
+        let events = writer
+            .drain_all()
+            .into_iter()
+            .filter_map(|data| state.decode(data).unwrap())
+            .collect::<Vec<_>>();
+        assert!(is_full_clear(&events[0], &events[1]));
+        assert!(is_full_clear(&events[1], &events[0]));
+        assert!(!is_full_clear(&events[0], &events[0]));
+        assert!(!is_full_clear(
+            &Cea608::EraseDisplay(Channel::ONE),
+            &Cea608::EndOfCaption(Channel::ONE)
+        ));
+    }
+
+    #[test]
+    fn events_split_by_channel() {
+        test_init_log();
+        let events = [
+            Cea608::EraseDisplay(Channel::ONE),
+            Cea608::EraseDisplay(Channel::TWO),
+            Cea608::CarriageReturn(Channel::TWO),
+            Cea608::CarriageReturn(Channel::ONE),
+        ];
+        let (one, two) = split_by_channel(&events);
+        assert_eq!(one, [events[0], events[3]]);
+        assert_eq!(two, [events[1], events[2]]);
+    }
+
+    #[test]
+    fn id_write_control() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        Id::CC3.write_control(tables::Control::EraseDisplayedMemory, &mut writer);
+        Id::CC3.write_control(tables::Control::DegreeSign, &mut writer);
+        assert_eq!(writer.pop(), [0x15, 0x2c]);
+        assert_eq!(writer.pop(), [0x91, 0x31]);
+        let control = Id::CC4.control_code(tables::Control::EndOfCaption);
+        assert_eq!(control.field(), Some(Field::TWO));
+        assert_eq!(control.channel(), Channel::TWO);
+    }
+
     #[test]
     fn writer_padding() {
         test_init_log();
@@ -478,6 +3465,174 @@ mod test {
         assert_eq!(writer.pop(), [0x80, 0x80]);
     }
 
+    #[test]
+    fn writer_push_styled() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        let red_italics = StyleAttrs {
+            color: tables::Color::Red,
+            underline: false,
+            italics: true,
+        };
+        writer
+            .push_styled(
+                Field::ONE,
+                Channel::ONE,
+                &[(red_italics, "A"), (StyleAttrs::default(), "b")],
+            )
+            .unwrap();
+        assert_eq!(writer.pop(), [0x91, 0xa8]);
+        assert_eq!(writer.pop(), [0x91, 0xae]);
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::Red, false, true)
+        );
+        assert_eq!(writer.pop(), [0xc1, 0x80]);
+        assert_eq!(writer.pop(), [0x91, 0x20]);
+        assert_eq!(writer.pop(), [0x62, 0x80]);
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::White, false, false)
+        );
+        assert!(writer.is_empty());
+
+        assert_eq!(
+            writer.push_styled(Field::ONE, Channel::ONE, &[(red_italics, "a€")]),
+            Err(WriterError::UnrepresentableChar('€'))
+        );
+        assert!(writer.is_empty());
+    }
+
+    #[test]
+    fn writer_push_delete_to_end_of_row() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push_delete_to_end_of_row(Field::TWO, Channel::TWO);
+        let data = writer.drain_all();
+        assert_eq!(data, [[0x9d, 0xa4]]);
+        let mut state = Cea608State::default();
+        assert_eq!(
+            state.decode(data[0]),
+            Ok(Some(Cea608::DeleteToEndOfRow(Channel::TWO)))
+        );
+        assert_eq!(state.last_received_field(), Some(Field::TWO));
+    }
+
+    #[test]
+    fn writer_push_reset() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push_reset(Field::TWO, Channel::TWO, Mode::RollUp3);
+        let pairs = writer.drain_all();
+        assert_eq!(
+            pairs,
+            [[0x9d, 0x26], [0x9d, 0x2c], [0x9d, 0xae], [0x1c, 0x70]]
+        );
+        let mut state = Cea608State::default();
+        let events = pairs
+            .into_iter()
+            .filter_map(|data| state.decode(data).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                Cea608::NewMode(Channel::TWO, Mode::RollUp3),
+                Cea608::EraseDisplay(Channel::TWO),
+                Cea608::EraseNonDisplay(Channel::TWO),
+                Cea608::Preamble(Channel::TWO, PreambleAddressCode::home()),
+            ]
+        );
+        assert_eq!(state.last_received_field(), Some(Field::TWO));
+    }
+
+    #[test]
+    fn writer_current_attributes() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::White, false, false)
+        );
+        let preamble =
+            PreambleAddressCode::new(3, true, tables::PreambleType::Color(tables::Color::Red));
+        Id::CC1.write_control(tables::Control::PreambleAddress(preamble), &mut writer);
+        // not emitted yet
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::White, false, false)
+        );
+        writer.pop();
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::Red, true, false)
+        );
+        Id::CC1.write_control(
+            tables::Control::MidRow(MidRow::new_italics(false)),
+            &mut writer,
+        );
+        writer.pop();
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::Red, false, true)
+        );
+        let midrow = MidRow::new_color(tables::Color::Green, false);
+        Id::CC1.write_control(tables::Control::MidRow(midrow), &mut writer);
+        writer.pop();
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::Green, false, false)
+        );
+        writer.reset();
+        assert_eq!(
+            writer.current_attributes(),
+            (tables::Color::White, false, false)
+        );
+    }
+
+    #[test]
+    fn writer_push_raw() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push(Code::LatinLowerA);
+        writer.push_raw([0x14, 0x2c]);
+        writer.push(Code::LatinLowerB);
+        writer.push(Code::LatinLowerC);
+        writer.push_raw([0x80, 0x80]);
+        assert_eq!(writer.n_codes(), 5);
+        assert_eq!(
+            writer.drain_all(),
+            [[0x61, 0x80], [0x14, 0x2c], [0x62, 0xe3], [0x80, 0x80]]
+        );
+        // the replacement character is not separated from the extended character
+        let mut writer = Cea608Writer::default();
+        writer.push_raw([0xc1, 0xc2]);
+        writer.push_char('É', Channel::ONE).unwrap();
+        let mut other = Cea608Writer::default();
+        other.push_raw([0x01, 0x02]);
+        writer.append(&mut other);
+        assert_eq!(
+            writer.drain_all(),
+            [[0xc1, 0xc2], [0x20, 0x80], [0x92, 0xa1], [0x01, 0x02]]
+        );
+    }
+
+    #[test]
+    fn writer_queue_latency_frames() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        assert_eq!(writer.queue_latency_frames(), 0);
+        writer.push_char('a', Channel::ONE).unwrap();
+        writer.push_char('É', Channel::ONE).unwrap();
+        Id::CC1.write_control(tables::Control::CarriageReturn, &mut writer);
+        writer.push_raw([0x80, 0x80]);
+        assert_eq!(writer.n_codes(), 4);
+        // [a, space], [É], [CR], [padding]
+        assert_eq!(writer.queue_latency_frames(), 4);
+        writer.pop();
+        assert_eq!(writer.queue_latency_frames(), 3);
+        assert_eq!(writer.queue_latency_frames(), writer.drain_all().len());
+    }
+
     #[test]
     fn writer_single_byte_code() {
         test_init_log();
@@ -541,6 +3696,23 @@ mod test {
         assert_eq!(writer.pop(), [0x80, 0x80]);
     }
 
+    #[test]
+    fn writer_single_byte_and_midrow() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push(Code::LatinCapitalA);
+        writer.push(Code::Control(ControlCode::new(
+            Field::ONE,
+            Channel::ONE,
+            tables::Control::MidRow(MidRow::new_color(tables::Color::Red, false)),
+        )));
+        writer.push(Code::LatinCapitalB);
+        assert_eq!(writer.pop(), [0xc1, 0x80]);
+        assert_eq!(writer.pop(), [0x91, 0xa8]);
+        assert_eq!(writer.pop(), [0xc2, 0x80]);
+        assert_eq!(writer.pop(), [0x80, 0x80]);
+    }
+
     #[test]
     fn writer_control() {
         test_init_log();
@@ -553,6 +3725,135 @@ mod test {
         assert_eq!(writer.pop(), [0x91, 0x31]);
         assert_eq!(writer.pop(), [0x80, 0x80]);
     }
+
+    #[test]
+    fn writer_push_char() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push_char('A', Channel::ONE).unwrap();
+        writer.push_char('é', Channel::ONE).unwrap();
+        assert_eq!(writer.pop(), [0xc1, 0xdc]);
+        writer.push_char('♪', Channel::ONE).unwrap();
+        assert_eq!(writer.pop(), [0x91, 0x37]);
+        writer.push_char('Á', Channel::TWO).unwrap();
+        assert_eq!(writer.pop(), [0x20, 0x80]);
+        assert_eq!(writer.pop(), [0x1a, 0x20]);
+        assert_eq!(
+            writer.push_char('\u{1F600}', Channel::ONE),
+            Err(WriterError::UnrepresentableChar('\u{1F600}'))
+        );
+        assert_eq!(writer.pop(), [0x80, 0x80]);
+    }
+
+    #[test]
+    fn writer_clear() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push_clear(Field::ONE, Channel::ONE, false);
+        assert_eq!(writer.pop(), [0x94, 0x2c]);
+        assert_eq!(writer.pop(), [0x80, 0x80]);
+        writer.push_clear(Field::TWO, Channel::TWO, true);
+        assert_eq!(writer.pop(), [0x9d, 0x2c]);
+        assert_eq!(writer.pop(), [0x9d, 0xae]);
+        assert_eq!(writer.pop(), [0x80, 0x80]);
+    }
+
+    #[test]
+    fn writer_remaining_capacity() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        assert_eq!(writer.remaining_capacity(2), 2);
+        writer.push(Code::LatinLowerA);
+        assert_eq!(writer.remaining_capacity(2), 1);
+        writer.push(Code::LatinLowerB);
+        writer.push(Code::LatinLowerC);
+        assert_eq!(writer.remaining_capacity(2), 0);
+        assert_eq!(writer.remaining_capacity(4), 1);
+    }
+
+    #[test]
+    fn writer_drain_all() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        assert!(writer.is_empty());
+        assert_eq!(writer.drain_all(), Vec::<[u8; 2]>::new());
+        writer.push(Code::LatinLowerA);
+        writer.push(Code::Control(ControlCode::new(
+            Field::ONE,
+            Channel::ONE,
+            tables::Control::Tilde,
+        )));
+        writer.push(Code::LatinLowerB);
+        assert!(!writer.is_empty());
+        assert_eq!(
+            writer.drain_all(),
+            [[0x61, 0x20], [0x13, 0x2f], [0x62, 0x80]]
+        );
+        assert!(writer.is_empty());
+    }
+
+    #[test]
+    fn writer_clone() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push(Code::LatinLowerA);
+        writer.push(Code::LatinLowerB);
+        let mut snapshot = writer.clone();
+        assert_eq!(writer, snapshot);
+        assert_eq!(writer.pop(), [0x61, 0x62]);
+        assert_ne!(writer, snapshot);
+        assert_eq!(snapshot.pop(), [0x61, 0x62]);
+        assert_eq!(writer, snapshot);
+    }
+
+    #[test]
+    fn writer_append() {
+        test_init_log();
+        let mut writer = Cea608Writer::default();
+        writer.push(Code::LatinLowerA);
+        let mut other = Cea608Writer::default();
+        other.push(Code::LatinLowerB);
+        other.push(Code::Control(ControlCode::new(
+            Field::ONE,
+            Channel::ONE,
+            tables::Control::Tilde,
+        )));
+        other.push(Code::LatinLowerC);
+        // leave the tilde as the look-ahead code
+        assert_eq!(other.pop(), [0x62, 0x20]);
+        writer.append(&mut other);
+        assert_eq!(other.n_codes(), 0);
+        assert_eq!(writer.n_codes(), 3);
+        assert_eq!(writer.pop(), [0x61, 0x20]);
+        assert_eq!(writer.pop(), [0x13, 0x2f]);
+        assert_eq!(writer.pop(), [0xe3, 0x80]);
+        assert_eq!(writer.pop(), [0x80, 0x80]);
+    }
+
+    #[test]
+    fn writer_append_pending_extended() {
+        test_init_log();
+        let mut other = Cea608Writer::default();
+        other.push_char('a', Channel::ONE).unwrap();
+        other.push_char('É', Channel::ONE).unwrap();
+        other.push_char('b', Channel::ONE).unwrap();
+        // 'a' and the replacement space for 'É', leaving 'É' as the look-ahead code
+        assert_eq!(other.pop(), [0x61, 0x20]);
+        let mut writer = Cea608Writer::default();
+        writer.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(writer.drain_all(), [[0x92, 0xa1], [0x62, 0x80]]);
+
+        let mut screen = screen::Cea608Screen::new(Channel::ONE);
+        let mut state = Cea608State::default();
+        screen.apply(&state.decode([0x94, 0x29]).unwrap().unwrap());
+        for data in [[0x61, 0x20], [0x92, 0xa1], [0x62, 0x80]] {
+            if let Some(cea608) = state.decode(data).unwrap() {
+                screen.apply(&cea608);
+            }
+        }
+        assert_eq!(screen.displayed_text()[0].1.trim_end(), "aÉb");
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,87 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for preparing CEA-608 byte pairs for insertion into line 21 of the vertical blanking
+//! interval (VBI).
+//!
+//! Each line 21 consists of:
+//! 1. A run-in clock of 7 sine wave cycles at 503 kHz.
+//! 2. Three start bits with the values 0, 0, 1.
+//! 3. The 16 data bits of the byte pair.  Each byte is transmitted least significant bit first
+//!    and the most significant bit is the odd parity bit.
+//!
+//! Generating the waveform itself is the responsibility of the VBI inserter.  This module only
+//! provides the byte pair and data bits in the expected order.
+
+use crate::tables::fix_parity;
+
+/// The start bits that are transmitted after the run-in clock and before the data bits
+pub const START_BITS: [bool; 3] = [false, false, true];
+
+/// Apply odd parity to a pair of 7-bit values.  The most significant bit of the input is ignored.
+/// This is the same as [`fix_parity`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::line21::with_parity;
+/// assert_eq!(with_parity([0x14, 0x2c]), [0x94, 0x2c]);
+/// assert_eq!(with_parity([0x00, 0x00]), [0x80, 0x80]);
+/// ```
+pub fn with_parity(data: [u8; 2]) -> [u8; 2] {
+    fix_parity(data)
+}
+
+/// The 16 data bits of a byte pair in the order they are transmitted on line 21.
+///
+/// The byte pair must already have parity applied, e.g. from
+/// [`Cea608Writer::pop`](crate::Cea608Writer::pop) or [`with_parity`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::line21::data_bits;
+/// let bits = data_bits([0x80, 0x01]);
+/// assert!(bits[7]);
+/// assert!(bits[8]);
+/// assert_eq!(bits.iter().filter(|b| **b).count(), 2);
+/// ```
+pub fn data_bits(data: [u8; 2]) -> [bool; 16] {
+    let mut bits = [false; 16];
+    for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = data[i / 8] & (1 << (i % 8)) != 0;
+    }
+    bits
+}
+
+/// The start bits followed by the data bits of a byte pair in the order they are transmitted on
+/// line 21 after the run-in clock.  See [`data_bits`].
+pub fn frame_bits(data: [u8; 2]) -> [bool; 19] {
+    let mut bits = [false; 19];
+    bits[..3].copy_from_slice(&START_BITS);
+    bits[3..].copy_from_slice(&data_bits(data));
+    bits
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn line21_frame_bits() {
+        test_init_log();
+        let data = with_parity([0x41, 0x42]);
+        assert_eq!(data, [0xc1, 0xc2]);
+        let bits = frame_bits(data);
+        assert_eq!(bits[..3], START_BITS);
+        let bytes = bits[3..].chunks(8).map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |acc, (i, bit)| acc | ((*bit as u8) << i))
+        });
+        assert_eq!(bytes.collect::<Vec<_>>(), data);
+    }
+}
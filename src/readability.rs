@@ -0,0 +1,116 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for checking whether captions are displayed long enough to be read
+
+/// The default maximum reading rate in words per minute used by [`check`]
+pub const DEFAULT_MAX_WORDS_PER_MINUTE: f64 = 160.0;
+
+/// The result of a readability check
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReadabilityVerdict {
+    /// The caption can be read in the time it is displayed
+    Readable {
+        /// The reading rate needed to read the caption in words per minute
+        words_per_minute: f64,
+    },
+    /// The caption is not displayed long enough to be read
+    TooFast {
+        /// The reading rate needed to read the caption in words per minute
+        words_per_minute: f64,
+    },
+}
+
+impl ReadabilityVerdict {
+    /// Whether the caption can be read in the time it is displayed
+    pub fn is_readable(&self) -> bool {
+        matches!(self, Self::Readable { .. })
+    }
+
+    /// The reading rate needed to read the caption in words per minute
+    pub fn words_per_minute(&self) -> f64 {
+        match self {
+            Self::Readable { words_per_minute } => *words_per_minute,
+            Self::TooFast { words_per_minute } => *words_per_minute,
+        }
+    }
+}
+
+/// Check whether `text` displayed for `duration_frames` frames at `fps` frames per second can be
+/// read at [`DEFAULT_MAX_WORDS_PER_MINUTE`].  See [`check_with_max`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::readability::check;
+/// // 10 words for 4 seconds at 30 fps is 150 words per minute
+/// let verdict = check("one two three four five six seven eight nine ten", 120, 30.0);
+/// assert!(verdict.is_readable());
+/// assert_eq!(verdict.words_per_minute(), 150.0);
+/// assert!(!check("one two three four five six seven eight nine ten", 60, 30.0).is_readable());
+/// ```
+pub fn check(text: &str, duration_frames: usize, fps: f64) -> ReadabilityVerdict {
+    check_with_max(text, duration_frames, fps, DEFAULT_MAX_WORDS_PER_MINUTE)
+}
+
+/// Check whether `text` displayed for `duration_frames` frames at `fps` frames per second can be
+/// read at `max_words_per_minute`.
+///
+/// Words are separated by whitespace.  Text without any words is always readable.  Other text
+/// with a duration of 0 or an `fps` that is not positive is never readable.
+pub fn check_with_max(
+    text: &str,
+    duration_frames: usize,
+    fps: f64,
+    max_words_per_minute: f64,
+) -> ReadabilityVerdict {
+    let n_words = text.split_whitespace().count();
+    let minutes = if fps > 0.0 {
+        duration_frames as f64 / fps / 60.0
+    } else {
+        0.0
+    };
+    let words_per_minute = if n_words == 0 {
+        0.0
+    } else if minutes > 0.0 {
+        n_words as f64 / minutes
+    } else {
+        f64::INFINITY
+    };
+    if words_per_minute <= max_words_per_minute {
+        ReadabilityVerdict::Readable { words_per_minute }
+    } else {
+        ReadabilityVerdict::TooFast { words_per_minute }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn readability_threshold() {
+        test_init_log();
+        // 4 words in 2 seconds
+        let text = "a caption to\nread";
+        assert_eq!(
+            check_with_max(text, 50, 25.0, 120.0),
+            ReadabilityVerdict::Readable {
+                words_per_minute: 120.0
+            }
+        );
+        assert_eq!(
+            check_with_max(text, 50, 25.0, 100.0),
+            ReadabilityVerdict::TooFast {
+                words_per_minute: 120.0
+            }
+        );
+        assert!(check(text, 50, 25.0).is_readable());
+        assert!(check("  ", 0, 25.0).is_readable());
+        assert!(!check(text, 0, 25.0).is_readable());
+        assert!(!check(text, 50, 0.0).is_readable());
+    }
+}
@@ -0,0 +1,814 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for rendering decoded [`Cea608`] events into a grid of characters
+
+use crate::tables::{Channel, Color, PreambleAddressCode};
+use crate::{Cea608, Mode, ModeTransition, Text};
+
+/// The number of rows on a CEA-608 screen
+pub const N_ROWS: u8 = 15;
+/// The number of columns on a CEA-608 screen
+pub const N_COLUMNS: u8 = 32;
+
+const DEFAULT_SAFE_MARGIN: u8 = 1;
+// the largest roll-up window must fit on the screen
+const MIN_ROWS: u8 = 4;
+
+/// Compute the cursor position as (row, column) after applying `event` in `mode`.
+///
+/// This is the cursor handling of [`Cea608Screen`] without any of the memory handling:
+/// - [`Cea608::Preamble`] moves the cursor to the preamble's row and column.  In the roll-up
+///   modes, the row is moved down if needed to fit the roll-up window on the screen.
+/// - [`Cea608::Text`], [`Cea608::MidRowChange`] and [`Cea608::TabOffset`] move the cursor forward
+///   by [`Cea608::columns_advanced`], stopping at the last column.
+/// - [`Cea608::Backspace`] moves the cursor back one column unless it is in the first column.
+/// - [`Cea608::CarriageReturn`] moves the cursor to the first column in the roll-up modes.
+/// - All other events do not move the cursor.
+///
+/// The channel of `event` is not checked.
+///
+/// Unlike [`Cea608Screen`], the position just past the last column is not modelled.  After a
+/// character is written to the last column, [`Cea608Screen`] places the cursor past the end,
+/// which [`Cea608Screen::cursor`] reports as the last column.  A following [`Cea608::Backspace`]
+/// then erases the last column and leaves the cursor there, while this function moves the cursor
+/// to the second to last column.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::{Cea608, Mode, screen::apply_cursor, tables::Channel};
+/// let pos = apply_cursor((14, 31), &Cea608::TabOffset(Channel::ONE, 2), Mode::PaintOn);
+/// assert_eq!(pos, (14, 31));
+/// let pos = apply_cursor((14, 10), &Cea608::CarriageReturn(Channel::ONE), Mode::RollUp2);
+/// assert_eq!(pos, (14, 0));
+/// ```
+pub fn apply_cursor(pos: (u8, u8), event: &Cea608, mode: Mode) -> (u8, u8) {
+    let (row, column) = pos;
+    match event {
+        Cea608::Preamble(_, preamble) => {
+            let min_row = mode.rollup_rows().map_or(0, |rollup_rows| rollup_rows - 1);
+            (preamble.row().clamp(min_row, N_ROWS - 1), preamble.column())
+        }
+        Cea608::Text(_) | Cea608::MidRowChange(_, _) | Cea608::TabOffset(_, _) => {
+            let column = column.saturating_add(event.columns_advanced());
+            (row, column.min(N_COLUMNS - 1))
+        }
+        Cea608::Backspace(_) => (row, column.saturating_sub(1)),
+        Cea608::CarriageReturn(_) if mode.is_rollup() => (row, 0),
+        _ => pos,
+    }
+}
+
+/// A single character on the screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cell {
+    /// The displayed character
+    pub char: char,
+    /// The foreground color of the character
+    pub color: Color,
+    /// Whether the character is underlined
+    pub underline: bool,
+    /// Whether the character is in italics
+    pub italics: bool,
+}
+
+type Memory = [[Option<Cell>; N_COLUMNS as usize]; N_ROWS as usize];
+
+/// A CEA-608 screen for a single [`Channel`].
+///
+/// Keeps track of the displayed and non-displayed memory and the cursor position as [`Cea608`]
+/// events are applied.
+///
+/// As specified, a [`Cea608::Backspace`] with the cursor in the first column does not wrap to the
+/// previous row and does not erase any character.  A [`Cea608::NewMode`] changes the memory as
+/// described by [`Mode::transition`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cea608Screen {
+    channel: Channel,
+    mode: Option<Mode>,
+    displayed: Memory,
+    non_displayed: Memory,
+    base_row: u8,
+    row: u8,
+    column: u8,
+    color: Color,
+    underline: bool,
+    italics: bool,
+    overflowed: bool,
+    out_of_bounds: bool,
+    safe_margin: u8,
+    rows: u8,
+    columns: u8,
+}
+
+impl Cea608Screen {
+    /// Construct a new empty screen for the provided [`Channel`]
+    pub fn new(channel: Channel) -> Self {
+        Self::with_dimensions(channel, N_ROWS, N_COLUMNS)
+    }
+
+    /// Construct a new empty screen for the provided [`Channel`] that is smaller than the
+    /// [`N_ROWS`] by [`N_COLUMNS`] CEA-608 screen.
+    ///
+    /// The screen consists of the top `rows` rows and the left `columns` columns.  Positioning
+    /// outside of the screen is clamped to the last row or column, see
+    /// [`last_out_of_bounds`](Self::last_out_of_bounds).  The number of rows is clamped to the
+    /// range [4, [`N_ROWS`]] so that every roll-up window fits, and the number of columns to the
+    /// range [1, [`N_COLUMNS`]].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::screen::Cea608Screen;
+    /// # use cea608_types::tables::Channel;
+    /// let screen = Cea608Screen::with_dimensions(Channel::ONE, 10, 20);
+    /// assert_eq!(screen.rows(), 10);
+    /// assert_eq!(screen.columns(), 20);
+    /// assert_eq!(screen.cursor(), (9, 0));
+    /// ```
+    pub fn with_dimensions(channel: Channel, rows: u8, columns: u8) -> Self {
+        let (color, underline, italics) = PreambleAddressCode::default_attributes();
+        let rows = rows.clamp(MIN_ROWS, N_ROWS);
+        let columns = columns.clamp(1, N_COLUMNS);
+        Self {
+            channel,
+            mode: None,
+            displayed: Self::empty_memory(),
+            non_displayed: Self::empty_memory(),
+            base_row: rows - 1,
+            row: rows - 1,
+            column: 0,
+            color,
+            underline,
+            italics,
+            overflowed: false,
+            out_of_bounds: false,
+            safe_margin: DEFAULT_SAFE_MARGIN.min((columns - 1) / 2),
+            rows,
+            columns,
+        }
+    }
+
+    /// The number of rows on this screen
+    pub fn rows(&self) -> u8 {
+        self.rows
+    }
+
+    /// The number of columns on this screen
+    pub fn columns(&self) -> u8 {
+        self.columns
+    }
+
+    /// The [`Channel`] this screen displays
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    /// The current [`Mode`].  No text is displayed until a mode has been set.
+    pub fn mode(&self) -> Option<Mode> {
+        self.mode
+    }
+
+    /// The current cursor position as (row, column)
+    pub fn cursor(&self) -> (u8, u8) {
+        (self.row, self.column.min(self.columns - 1))
+    }
+
+    /// The bottom row of the roll-up window
+    pub fn base_row(&self) -> u8 {
+        self.base_row
+    }
+
+    /// Set the bottom row of the roll-up window.  Any rows currently in the roll-up window are
+    /// moved to the new position.
+    ///
+    /// The base row is clamped so that the roll-up window fits on the screen.  e.g. with
+    /// [`Mode::RollUp4`], the base row is at least 3.  A [`Cea608::Preamble`] received in a roll-up
+    /// mode also sets the base row.
+    pub fn set_base_row(&mut self, base_row: u8) {
+        let base_row = self.clamp_base_row(base_row);
+        if let Some(rollup_rows) = self.rollup_rows() {
+            self.move_window(base_row, rollup_rows);
+            self.row = base_row;
+        }
+        self.base_row = base_row;
+    }
+
+    /// Whether the last applied event wrote a character past the end of the row.
+    ///
+    /// Characters written past the last column replace the character in the last column.
+    pub fn last_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Whether the last applied event positioned the cursor outside of the screen, e.g. a
+    /// [`Cea608::Preamble`] for a row or column outside of a screen constructed with
+    /// [`with_dimensions`](Self::with_dimensions), or a [`Cea608::TabOffset`] past the last
+    /// column.
+    ///
+    /// The cursor is placed on the last row or column instead.
+    pub fn last_out_of_bounds(&self) -> bool {
+        self.out_of_bounds
+    }
+
+    /// Set the number of columns on each side of a row that are outside of the safe area.
+    ///
+    /// Text in these columns may be cut off by the overscan of a display.  The default of 1 results
+    /// in columns 1 to 30 being safe on a screen with [`N_COLUMNS`] columns.  The margin is
+    /// clamped to leave at least one safe column.
+    pub fn set_safe_margin(&mut self, margin: u8) {
+        self.safe_margin = margin.min((self.columns - 1) / 2);
+    }
+
+    /// The number of columns on each side of a row that are outside of the safe area
+    pub fn safe_margin(&self) -> u8 {
+        self.safe_margin
+    }
+
+    /// The range of columns that are within the safe area.  See
+    /// [`set_safe_margin`](Self::set_safe_margin).
+    pub fn safe_columns(&self) -> std::ops::RangeInclusive<u8> {
+        self.safe_margin..=self.columns - 1 - self.safe_margin
+    }
+
+    /// Whether `column` is within the safe area.  See [`set_safe_margin`](Self::set_safe_margin).
+    pub fn is_within_safe_columns(&self, column: u8) -> bool {
+        self.safe_columns().contains(&column)
+    }
+
+    /// The [`Cell`] at the provided position in the displayed memory
+    pub fn displayed_cell(&self, row: u8, column: u8) -> Option<Cell> {
+        *self.displayed.get(row as usize)?.get(column as usize)?
+    }
+
+    /// The number of rows in the displayed memory that contain any characters
+    pub fn rows_used(&self) -> u8 {
+        self.displayed
+            .iter()
+            .filter(|cells| cells.iter().any(|cell| cell.is_some()))
+            .count() as u8
+    }
+
+    /// The text of each row in the displayed memory that contains any characters.
+    ///
+    /// Empty cells before the last character of a row are returned as spaces.
+    pub fn displayed_text(&self) -> Vec<(u8, String)> {
+        self.displayed
+            .iter()
+            .enumerate()
+            .filter_map(|(row, cells)| {
+                let len = cells.iter().rposition(|cell| cell.is_some())? + 1;
+                let text = cells[..len]
+                    .iter()
+                    .map(|cell| cell.map_or(' ', |cell| cell.char))
+                    .collect();
+                Some((row as u8, text))
+            })
+            .collect()
+    }
+
+    /// Apply a [`Cea608`] event to this screen.  Events for a different [`Channel`] and
+    /// [`Cea608::TextService`] events are ignored.
+    pub fn apply(&mut self, cea608: &Cea608) {
+        if cea608.channel() != self.channel {
+            return;
+        }
+        self.overflowed = false;
+        self.out_of_bounds = false;
+        match *cea608 {
+            Cea608::Text(text) => self.text(text),
+            Cea608::NewMode(_, mode) => self.new_mode(mode),
+            Cea608::EraseDisplay(_) => self.displayed = Self::empty_memory(),
+            Cea608::EraseNonDisplay(_) => self.non_displayed = Self::empty_memory(),
+            Cea608::CarriageReturn(_) => self.carriage_return(),
+            // a backspace in the first column is ignored
+            Cea608::Backspace(_) if self.column == 0 => (),
+            Cea608::Backspace(_) => {
+                self.column -= 1;
+                let (row, column) = (self.row, self.column);
+                self.memory()[row as usize][column as usize] = None;
+            }
+            Cea608::EndOfCaption(_) => {
+                std::mem::swap(&mut self.displayed, &mut self.non_displayed);
+            }
+            Cea608::TabOffset(_, offset) => {
+                self.column = self.clamp_column(self.column.saturating_add(offset));
+            }
+            Cea608::DeleteToEndOfRow(_) => {
+                let (row, column) = (self.row, self.column);
+                self.memory()[row as usize][column as usize..].fill(None);
+            }
+            Cea608::Preamble(_, preamble) => {
+                if preamble.row() >= self.rows {
+                    debug!("Row {} is outside of the screen", preamble.row());
+                    self.out_of_bounds = true;
+                }
+                if self.rollup_rows().is_some() {
+                    self.set_base_row(preamble.row());
+                } else {
+                    self.row = preamble.row().min(self.rows - 1);
+                }
+                self.column = self.clamp_column(preamble.column());
+                self.color = preamble.color();
+                self.underline = preamble.underline();
+                self.italics = preamble.italics();
+            }
+            Cea608::MidRowChange(_, midrow) => {
+                if let Some(color) = midrow.color() {
+                    self.color = color;
+                    self.italics = false;
+                } else {
+                    self.italics = true;
+                }
+                self.underline = midrow.underline();
+                self.put_char(' ');
+            }
+            Cea608::RawControl(_, _, _) | Cea608::TextService(_, _) => (),
+        }
+    }
+
+    /// Apply a sequence of [`Cea608`] events to this screen in order.  Events for a different
+    /// [`Channel`] are ignored.
+    pub fn apply_all(&mut self, events: &[Cea608]) {
+        for cea608 in events {
+            self.apply(cea608);
+        }
+    }
+
+    /// Reset as if it was a newly created instance for the same [`Channel`] and dimensions.  The
+    /// [`safe_margin`](Self::safe_margin) is kept.
+    pub fn reset(&mut self) {
+        *self = Self {
+            safe_margin: self.safe_margin,
+            ..Self::with_dimensions(self.channel, self.rows, self.columns)
+        };
+    }
+
+    fn empty_memory() -> Memory {
+        [[None; N_COLUMNS as usize]; N_ROWS as usize]
+    }
+
+    fn memory(&mut self) -> &mut Memory {
+        if self.mode == Some(Mode::PopOn) {
+            &mut self.non_displayed
+        } else {
+            &mut self.displayed
+        }
+    }
+
+    fn rollup_rows(&self) -> Option<u8> {
+        self.mode.and_then(|mode| mode.rollup_rows())
+    }
+
+    fn clamp_base_row(&self, base_row: u8) -> u8 {
+        let min_row = self.rollup_rows().map_or(0, |rollup_rows| rollup_rows - 1);
+        base_row.clamp(min_row, self.rows - 1)
+    }
+
+    fn clamp_column(&mut self, column: u8) -> u8 {
+        if column >= self.columns {
+            debug!("Column {column} is outside of the screen");
+            self.out_of_bounds = true;
+        }
+        column.min(self.columns - 1)
+    }
+
+    fn move_window(&mut self, base_row: u8, rollup_rows: u8) {
+        if base_row == self.base_row {
+            return;
+        }
+        let old_top = (self.base_row + 1).saturating_sub(rollup_rows) as usize;
+        let window = self.displayed[old_top..=self.base_row as usize].to_vec();
+        let new_top = base_row as usize + 1 - window.len();
+        self.displayed = Self::empty_memory();
+        self.displayed[new_top..=base_row as usize].copy_from_slice(&window);
+    }
+
+    fn new_mode(&mut self, mode: Mode) {
+        let was_rollup = self.rollup_rows();
+        let transition = match self.mode {
+            Some(prev) => prev.transition(mode),
+            None if mode.is_rollup() => ModeTransition::ClearScreen,
+            None => ModeTransition::PreserveRows,
+        };
+        self.mode = Some(mode);
+        if transition == ModeTransition::ClearScreen {
+            self.displayed = Self::empty_memory();
+            self.non_displayed = Self::empty_memory();
+            self.column = 0;
+        }
+        if !mode.is_rollup() {
+            return;
+        }
+        // entering roll-up places the cursor on the base row
+        let base_row = self.clamp_base_row(self.base_row);
+        if let Some(rollup_rows) = was_rollup {
+            // more roll-up rows may require moving the window down
+            self.move_window(base_row, rollup_rows);
+        }
+        self.base_row = base_row;
+        self.row = base_row;
+    }
+
+    fn carriage_return(&mut self) {
+        let Some(rollup_rows) = self.rollup_rows() else {
+            return;
+        };
+        let base_row = self.base_row as usize;
+        let top_row = (base_row + 1).saturating_sub(rollup_rows as usize);
+        // rows above the roll-up window are removed
+        for row in self.displayed[..top_row].iter_mut() {
+            row.fill(None);
+        }
+        self.displayed[top_row..=base_row].rotate_left(1);
+        self.displayed[base_row].fill(None);
+        self.column = 0;
+    }
+
+    fn put_char(&mut self, c: char) {
+        let cell = Cell {
+            char: c,
+            color: self.color,
+            underline: self.underline,
+            italics: self.italics,
+        };
+        // the cursor may be placed just past the last column after writing to the last column
+        if self.column >= self.columns {
+            debug!("Row {} overflowed", self.row);
+            self.overflowed = true;
+            self.column = self.columns - 1;
+        }
+        let (row, column) = (self.row, self.column);
+        self.memory()[row as usize][column as usize] = Some(cell);
+        self.column += 1;
+    }
+
+    fn text(&mut self, text: Text) {
+        if self.mode.is_none() {
+            return;
+        }
+        if text.needs_backspace {
+            self.column = self.column.saturating_sub(1);
+        }
+        for c in [text.char1, text.char2].into_iter().flatten() {
+            self.put_char(c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tables::{MidRow, PreambleType};
+    use crate::tests::*;
+
+    fn text(s: &str) -> Vec<Cea608> {
+        s.chars()
+            .map(|c| {
+                Cea608::Text(Text {
+                    needs_backspace: false,
+                    char1: Some(c),
+                    char2: None,
+                    channel: Channel::ONE,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn screen_pop_on() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.apply_all(&[
+            Cea608::NewMode(Channel::ONE, Mode::PopOn),
+            Cea608::Preamble(
+                Channel::ONE,
+                PreambleAddressCode::new(14, false, PreambleType::Indent4),
+            ),
+        ]);
+        screen.apply_all(&text("Hello"));
+        assert_eq!(screen.displayed_text(), []);
+        assert_eq!(screen.cursor(), (14, 9));
+        screen.apply(&Cea608::EndOfCaption(Channel::ONE));
+        assert_eq!(screen.displayed_text(), [(14, String::from("    Hello"))]);
+        screen.apply(&Cea608::EraseDisplay(Channel::ONE));
+        assert_eq!(screen.displayed_text(), []);
+    }
+
+    #[test]
+    fn screen_rows_used() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::PaintOn));
+        assert_eq!(screen.rows_used(), 0);
+        for row in [3, 5] {
+            screen.apply(&Cea608::Preamble(
+                Channel::ONE,
+                PreambleAddressCode::new(row, false, PreambleType::Indent8),
+            ));
+            screen.apply_all(&text("row"));
+        }
+        assert_eq!(screen.rows_used(), 2);
+        screen.apply(&Cea608::EraseDisplay(Channel::ONE));
+        assert_eq!(screen.rows_used(), 0);
+    }
+
+    #[test]
+    fn screen_roll_up() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::RollUp2));
+        screen.apply_all(&text("one"));
+        screen.apply(&Cea608::CarriageReturn(Channel::ONE));
+        screen.apply_all(&text("two"));
+        screen.apply(&Cea608::CarriageReturn(Channel::ONE));
+        screen.apply_all(&text("three"));
+        assert_eq!(
+            screen.displayed_text(),
+            [(13, String::from("two")), (14, String::from("three"))]
+        );
+    }
+
+    #[test]
+    fn screen_roll_up_to_pop_on() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::RollUp2));
+        screen.apply_all(&text("one"));
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::RollUp3));
+        assert_eq!(screen.displayed_text(), [(14, String::from("one"))]);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::PopOn));
+        assert_eq!(screen.displayed_text(), []);
+        assert_eq!(screen.cursor(), (14, 0));
+    }
+
+    #[test]
+    fn screen_dimensions() {
+        test_init_log();
+        let mut screen = Cea608Screen::with_dimensions(Channel::ONE, 8, 10);
+        assert_eq!((screen.rows(), screen.columns()), (8, 10));
+        assert_eq!(screen.safe_columns(), 1..=8);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::PaintOn));
+        screen.apply(&Cea608::Preamble(
+            Channel::ONE,
+            PreambleAddressCode::new(5, false, PreambleType::Indent4),
+        ));
+        assert!(!screen.last_out_of_bounds());
+        assert_eq!(screen.cursor(), (5, 4));
+        screen.apply(&Cea608::TabOffset(Channel::ONE, 3));
+        assert!(!screen.last_out_of_bounds());
+        screen.apply(&Cea608::TabOffset(Channel::ONE, 3));
+        assert!(screen.last_out_of_bounds());
+        assert_eq!(screen.cursor(), (5, 9));
+        screen.apply(&Cea608::TabOffset(Channel::ONE, 255));
+        assert!(screen.last_out_of_bounds());
+        assert_eq!(screen.cursor(), (5, 9));
+        screen.apply(&Cea608::Preamble(
+            Channel::ONE,
+            PreambleAddressCode::new(12, false, PreambleType::Indent12),
+        ));
+        assert!(screen.last_out_of_bounds());
+        assert_eq!(screen.cursor(), (7, 9));
+        screen.apply_all(&text("abc"));
+        assert!(screen.last_overflowed());
+        assert_eq!(screen.displayed_text(), [(7, String::from("         c"))]);
+
+        let mut screen = Cea608Screen::with_dimensions(Channel::ONE, 1, 40);
+        assert_eq!((screen.rows(), screen.columns()), (4, N_COLUMNS));
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::RollUp4));
+        screen.apply(&Cea608::Preamble(
+            Channel::ONE,
+            PreambleAddressCode::new(14, false, PreambleType::Indent0),
+        ));
+        assert!(screen.last_out_of_bounds());
+        assert_eq!(screen.cursor(), (3, 0));
+        screen.reset();
+        assert_eq!((screen.rows(), screen.columns()), (4, N_COLUMNS));
+    }
+
+    #[test]
+    fn screen_overflow() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::RollUp2));
+        let events = text(&"abcdefghij".repeat(4));
+        for (i, event) in events.iter().enumerate() {
+            screen.apply(event);
+            assert_eq!(screen.last_overflowed(), i >= 32);
+        }
+        assert_eq!(screen.cursor(), (14, 31));
+        let mut expected = "abcdefghij".repeat(4);
+        expected.replace_range(31..39, "");
+        assert_eq!(screen.displayed_text(), [(14, expected)]);
+        screen.apply(&Cea608::Backspace(Channel::ONE));
+        assert!(!screen.last_overflowed());
+        assert_eq!(screen.displayed_cell(14, 31), None);
+    }
+
+    #[test]
+    fn screen_roll_up_base_row() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.set_base_row(15);
+        assert_eq!(screen.base_row(), 14);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::RollUp4));
+        screen.apply_all(&text("one"));
+        screen.apply(&Cea608::CarriageReturn(Channel::ONE));
+        screen.apply_all(&text("two"));
+        assert_eq!(
+            screen.displayed_text(),
+            [(13, String::from("one")), (14, String::from("two"))]
+        );
+        // the window cannot extend above the top row
+        screen.set_base_row(1);
+        assert_eq!(screen.base_row(), 3);
+        assert_eq!(
+            screen.displayed_text(),
+            [(2, String::from("one")), (3, String::from("two"))]
+        );
+        // a preamble moves the window
+        screen.apply(&Cea608::Preamble(
+            Channel::ONE,
+            PreambleAddressCode::new(7, false, PreambleType::Indent0),
+        ));
+        assert_eq!(screen.base_row(), 7);
+        assert_eq!(screen.cursor(), (7, 0));
+        screen.apply(&Cea608::CarriageReturn(Channel::ONE));
+        screen.apply_all(&text("three"));
+        assert_eq!(
+            screen.displayed_text(),
+            [
+                (5, String::from("one")),
+                (6, String::from("two")),
+                (7, String::from("three"))
+            ]
+        );
+    }
+
+    #[test]
+    fn screen_backspace_at_column_0() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.apply(&Cea608::NewMode(Channel::ONE, Mode::PaintOn));
+        screen.apply(&Cea608::Preamble(
+            Channel::ONE,
+            PreambleAddressCode::new(0, false, PreambleType::Indent0),
+        ));
+        screen.apply(&Cea608::Backspace(Channel::ONE));
+        assert_eq!(screen.cursor(), (0, 0));
+        screen.apply_all(&text("a"));
+        screen.apply(&Cea608::Preamble(
+            Channel::ONE,
+            PreambleAddressCode::new(0, false, PreambleType::Indent0),
+        ));
+        screen.apply(&Cea608::Backspace(Channel::ONE));
+        assert_eq!(screen.cursor(), (0, 0));
+        assert_eq!(screen.displayed_text(), [(0, String::from("a"))]);
+        screen.apply(&Cea608::TabOffset(Channel::ONE, 1));
+        screen.apply(&Cea608::Backspace(Channel::ONE));
+        assert_eq!(screen.cursor(), (0, 0));
+        assert_eq!(screen.displayed_text(), []);
+    }
+
+    #[test]
+    fn screen_safe_columns() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        assert_eq!(screen.safe_columns(), 1..=30);
+        assert!(!screen.is_within_safe_columns(0));
+        assert!(screen.is_within_safe_columns(1));
+        assert!(screen.is_within_safe_columns(30));
+        assert!(!screen.is_within_safe_columns(31));
+        screen.set_safe_margin(4);
+        screen.reset();
+        assert_eq!(screen.safe_margin(), 4);
+        assert_eq!(screen.safe_columns(), 4..=27);
+        screen.set_safe_margin(0);
+        assert!(screen.is_within_safe_columns(0));
+        assert!(screen.is_within_safe_columns(31));
+        assert!(!screen.is_within_safe_columns(32));
+        screen.set_safe_margin(100);
+        assert_eq!(screen.safe_columns(), 15..=16);
+    }
+
+    #[test]
+    fn cursor_preamble() {
+        test_init_log();
+        let preamble = Cea608::Preamble(
+            Channel::ONE,
+            PreambleAddressCode::new(1, false, PreambleType::Indent12),
+        );
+        assert_eq!(apply_cursor((14, 3), &preamble, Mode::PopOn), (1, 12));
+        assert_eq!(apply_cursor((14, 3), &preamble, Mode::RollUp4), (3, 12));
+    }
+
+    #[test]
+    fn cursor_tab_offset() {
+        test_init_log();
+        let tab = Cea608::TabOffset(Channel::ONE, 3);
+        assert_eq!(apply_cursor((2, 3), &tab, Mode::PaintOn), (2, 6));
+        assert_eq!(apply_cursor((2, 30), &tab, Mode::PaintOn), (2, 31));
+    }
+
+    #[test]
+    fn cursor_text() {
+        test_init_log();
+        let events = text("ab");
+        assert_eq!(apply_cursor((5, 0), &events[0], Mode::PaintOn), (5, 1));
+        let pair = Cea608::Text(Text {
+            needs_backspace: false,
+            char1: Some('a'),
+            char2: Some('b'),
+            channel: Channel::ONE,
+        });
+        assert_eq!(apply_cursor((5, 0), &pair, Mode::PaintOn), (5, 2));
+        assert_eq!(apply_cursor((5, 31), &pair, Mode::PaintOn), (5, 31));
+        let extended = Cea608::Text(Text {
+            needs_backspace: true,
+            char1: Some('É'),
+            char2: None,
+            channel: Channel::ONE,
+        });
+        assert_eq!(apply_cursor((5, 4), &extended, Mode::PaintOn), (5, 4));
+        let midrow = Cea608::MidRowChange(Channel::ONE, crate::tables::MidRow::new_italics(false));
+        assert_eq!(apply_cursor((5, 4), &midrow, Mode::PaintOn), (5, 5));
+    }
+
+    #[test]
+    fn cursor_backspace() {
+        test_init_log();
+        let backspace = Cea608::Backspace(Channel::ONE);
+        assert_eq!(apply_cursor((5, 4), &backspace, Mode::PaintOn), (5, 3));
+        assert_eq!(apply_cursor((5, 0), &backspace, Mode::PaintOn), (5, 0));
+    }
+
+    #[test]
+    fn cursor_matches_screen() {
+        test_init_log();
+        let mode = Mode::PaintOn;
+        let mut screen = Cea608Screen::new(Channel::ONE);
+        screen.apply(&Cea608::NewMode(Channel::ONE, mode));
+        let mut pos = screen.cursor();
+        let mut events = vec![
+            Cea608::Preamble(
+                Channel::ONE,
+                PreambleAddressCode::new(14, false, PreambleType::Indent24),
+            ),
+            Cea608::TabOffset(Channel::ONE, 2),
+        ];
+        events.extend(text("ab"));
+        events.push(Cea608::Backspace(Channel::ONE));
+        events.push(Cea608::MidRowChange(
+            Channel::ONE,
+            MidRow::new_color(Color::Red, false),
+        ));
+        events.extend(text("cdef"));
+        for event in &events {
+            screen.apply(event);
+            pos = apply_cursor(pos, event, mode);
+            assert_eq!(pos, screen.cursor(), "{event:?}");
+        }
+        assert_eq!(pos, (14, 31));
+        // the screen keeps the cursor past the last column, see apply_cursor
+        let backspace = Cea608::Backspace(Channel::ONE);
+        screen.apply(&backspace);
+        assert_eq!(screen.cursor(), (14, 31));
+        assert_eq!(apply_cursor(pos, &backspace, mode), (14, 30));
+    }
+
+    #[test]
+    fn cursor_carriage_return() {
+        test_init_log();
+        let cr = Cea608::CarriageReturn(Channel::ONE);
+        assert_eq!(apply_cursor((14, 9), &cr, Mode::RollUp3), (14, 0));
+        assert_eq!(apply_cursor((14, 9), &cr, Mode::PopOn), (14, 9));
+        let edm = Cea608::EraseDisplay(Channel::ONE);
+        assert_eq!(apply_cursor((14, 9), &edm, Mode::RollUp3), (14, 9));
+    }
+
+    #[test]
+    fn screen_other_channel() {
+        test_init_log();
+        let mut screen = Cea608Screen::new(Channel::TWO);
+        screen.apply(&Cea608::NewMode(Channel::TWO, Mode::PaintOn));
+        screen.apply_all(&text("ignored"));
+        screen.apply(&Cea608::Text(Text {
+            needs_backspace: false,
+            char1: Some('a'),
+            char2: Some('b'),
+            channel: Channel::TWO,
+        }));
+        screen.apply(&Cea608::Text(Text {
+            needs_backspace: true,
+            char1: Some('é'),
+            char2: None,
+            channel: Channel::TWO,
+        }));
+        assert_eq!(screen.displayed_text(), [(14, String::from("aé"))]);
+        let cell = screen.displayed_cell(14, 1).unwrap();
+        assert_eq!(cell.char, 'é');
+        assert_eq!(cell.color, Color::White);
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (C) 2024 Matthew Waters <matthew@centricular.com>
+//
+// Licensed under the MIT license <LICENSE-MIT> or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Module for gathering statistics about decoded [`Cea608`] events
+
+use crate::{Cea608, Mode};
+
+/// Statistics gathered from a sequence of decoded [`Cea608`] events
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    n_events: usize,
+    n_mode: [usize; 5],
+    last_data: Option<[u8; 2]>,
+    repeat_run: usize,
+    max_repeat_run: usize,
+}
+
+impl Stats {
+    /// Update the statistics with a decoded [`Cea608`] event
+    pub fn record(&mut self, cea608: &Cea608) {
+        self.n_events += 1;
+        if let Cea608::NewMode(_, mode) = cea608 {
+            self.n_mode[Self::mode_idx(*mode)] += 1;
+        }
+    }
+
+    /// Update the statistics with a received byte pair, e.g. before passing it to
+    /// [`Cea608State::decode`](crate::Cea608State::decode).  The statistics returned by
+    /// [`Cea608State::stats`](crate::Cea608State::stats) are updated automatically.
+    ///
+    /// Only used for tracking the [`max_repeat_run`](Self::max_repeat_run).
+    pub fn record_data(&mut self, data: [u8; 2]) {
+        if data[0] & 0x7f == 0 && data[1] & 0x7f == 0 {
+            self.last_data = None;
+            self.repeat_run = 0;
+            return;
+        }
+        if self.last_data == Some(data) {
+            self.repeat_run += 1;
+        } else {
+            self.last_data = Some(data);
+            self.repeat_run = 1;
+        }
+        self.max_repeat_run = self.max_repeat_run.max(self.repeat_run);
+    }
+
+    /// The longest run of identical consecutive non-padding byte pairs recorded with
+    /// [`record_data`](Self::record_data).
+    ///
+    /// Doubled control codes produce a run of 2.  Much longer runs can indicate a stuck encoder.
+    /// Padding ends a run.
+    pub fn max_repeat_run(&self) -> usize {
+        self.max_repeat_run
+    }
+
+    /// The number of events recorded
+    pub fn n_events(&self) -> usize {
+        self.n_events
+    }
+
+    /// The number of commands that selected `mode`.
+    ///
+    /// [`Mode::PopOn`] counts Resume Caption Loading commands and [`Mode::PaintOn`] counts Resume
+    /// Direct Captioning commands.
+    pub fn n_mode_commands(&self, mode: Mode) -> usize {
+        self.n_mode[Self::mode_idx(mode)]
+    }
+
+    /// The number of commands that selected any mode
+    pub fn n_all_mode_commands(&self) -> usize {
+        self.n_mode.iter().sum()
+    }
+
+    /// Reset all statistics to 0
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn mode_idx(mode: Mode) -> usize {
+        match mode {
+            Mode::PopOn => 0,
+            Mode::PaintOn => 1,
+            Mode::RollUp2 => 2,
+            Mode::RollUp3 => 3,
+            Mode::RollUp4 => 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+    use crate::Cea608State;
+
+    #[test]
+    fn stats_mode_commands() {
+        test_init_log();
+        let mut state = Cea608State::default();
+        let mut stats = Stats::default();
+        // RCL, RDC, RCL, RU3, EDM
+        for data in [
+            [0x94, 0x20],
+            [0x94, 0x29],
+            [0x94, 0x20],
+            [0x94, 0x26],
+            [0x94, 0x2c],
+        ] {
+            if let Some(cea608) = state.decode(data).unwrap() {
+                stats.record(&cea608);
+            }
+        }
+        assert_eq!(stats.n_events(), 5);
+        assert_eq!(stats.n_mode_commands(Mode::PopOn), 2);
+        assert_eq!(stats.n_mode_commands(Mode::PaintOn), 1);
+        assert_eq!(stats.n_mode_commands(Mode::RollUp2), 0);
+        assert_eq!(stats.n_mode_commands(Mode::RollUp3), 1);
+        assert_eq!(stats.n_mode_commands(Mode::RollUp4), 0);
+        assert_eq!(stats.n_all_mode_commands(), 4);
+        stats.reset();
+        assert_eq!(stats.n_all_mode_commands(), 0);
+    }
+
+    #[test]
+    fn stats_max_repeat_run() {
+        test_init_log();
+        let mut stats = Stats::default();
+        assert_eq!(stats.max_repeat_run(), 0);
+        // doubled EDM
+        stats.record_data([0x94, 0x2c]);
+        stats.record_data([0x94, 0x2c]);
+        assert_eq!(stats.max_repeat_run(), 2);
+        // stuck 'AB' interrupted by padding
+        for _ in 0..5 {
+            stats.record_data([0xc1, 0xc2]);
+        }
+        stats.record_data([0x80, 0x80]);
+        stats.record_data([0xc1, 0xc2]);
+        stats.record_data([0xc1, 0xc2]);
+        assert_eq!(stats.max_repeat_run(), 5);
+        stats.reset();
+        assert_eq!(stats.max_repeat_run(), 0);
+    }
+}
@@ -100,28 +100,47 @@ impl ControlCode {
         self.control
     }
 
-    fn write(&self) -> [u8; 2] {
-        let mut data;
+    /// The length in bytes of this [`ControlCode`].
+    ///
+    /// Every control code is 2 bytes, including the special and extended characters such as '♪'.
+    /// Only the basic characters are a single byte, see [`Code::byte_len`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Channel, Control, ControlCode, Field};
+    /// let note = ControlCode::new(Field::ONE, Channel::ONE, Control::MusicalNote);
+    /// assert_eq!(note.byte_len(), 2);
+    /// ```
+    pub const fn byte_len(&self) -> usize {
+        2
+    }
+
+    /// Whether the combination of [`Field`], [`Channel`] and [`Control`] can be represented.
+    ///
+    /// The miscellaneous control codes (e.g. [`Control::ResumeCaptionLoading`] through to
+    /// [`Control::EndOfCaption`]) are encoded differently in each field and therefore require a
+    /// [`Field`] to be set.  All other control codes are encoded identically in both fields and
+    /// are valid with or without a [`Field`].  [`Control::Unknown`] is never valid.
+    ///
+    /// XDS data is only valid in field 2, but is not represented by a [`ControlCode`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Channel, Control, ControlCode, Field};
+    /// let eoc = ControlCode::new(Field::TWO, Channel::ONE, Control::EndOfCaption);
+    /// assert!(eoc.is_valid_combination());
+    /// ```
+    pub fn is_valid_combination(&self) -> bool {
         match self.control {
-            Control::Unknown(unk) => {
-                data = [unk[0], unk[1]];
-            }
-            Control::MidRow(midrow) => {
-                data = midrow.to_bytes();
-            }
-            Control::PreambleAddress(preamble) => {
-                data = preamble.to_bytes();
-            }
-            _ => {
-                if let Ok(idx) = CONTROL_MAP_TABLE
-                    .binary_search_by_key(&self.control, |control_map| control_map.control)
-                {
-                    data = CONTROL_MAP_TABLE[idx].cea608_bytes;
-                } else {
-                    unreachable!();
-                }
-            }
+            Control::Unknown(_) => false,
+            control if control.is_field_specific() => self.field.is_some(),
+            _ => true,
         }
+    }
+
+    fn write(&self) -> [u8; 2] {
+        let (byte0, byte1) = self.control.to_code_bytes();
+        let mut data = [byte0, byte1];
         if (0x20..=0x2f).contains(&data[1]) && data[0] == 0x14 && self.field == Some(Field::TWO) {
             data[0] |= 0x01;
         }
@@ -184,6 +203,23 @@ impl MidRow {
         matches!(self.color, MidRowColor::Italics)
     }
 
+    /// The second byte of this mid row command without parity, e.g. for comparing against the
+    /// tables in the specification.  The first byte is always `0x11` for channel 1 and `0x19` for
+    /// channel 2.
+    ///
+    /// The byte is laid out as `0b0010_cccu` where `ccc` is the color (white, green, blue, cyan,
+    /// red, yellow, magenta, or 7 for italics) and `u` signals underline.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Color, MidRow};
+    /// assert_eq!(MidRow::new_color(Color::Red, true).raw_bits(), 0x29);
+    /// assert_eq!(MidRow::new_italics(false).raw_bits(), 0x2e);
+    /// ```
+    pub fn raw_bits(&self) -> u8 {
+        self.to_bytes()[1]
+    }
+
     fn to_bytes(self) -> [u8; 2] {
         let underline = if self.underline { 0x01 } else { 0x0 };
         let color = match self.color {
@@ -438,12 +474,69 @@ pub enum Control {
 }
 
 impl Control {
+    fn is_field_specific(&self) -> bool {
+        CONTROL_MAP_TABLE
+            .binary_search_by_key(self, |control_map| control_map.control)
+            .is_ok_and(|idx| {
+                let bytes = CONTROL_MAP_TABLE[idx].cea608_bytes;
+                bytes[0] == 0x14 && (0x20..=0x2f).contains(&bytes[1])
+            })
+    }
+
+    /// The canonical command bytes of this [`Control`] without parity.
+    ///
+    /// The bytes are those for [`Channel::ONE`] in [`Field::ONE`].  [`ControlCode`] adjusts the
+    /// first byte for other channels and fields.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::Control;
+    /// assert_eq!(Control::EndOfCaption.to_code_bytes(), (0x14, 0x2f));
+    /// assert_eq!(Control::TabOffset2.to_code_bytes(), (0x17, 0x22));
+    /// ```
+    pub fn to_code_bytes(&self) -> (u8, u8) {
+        let data = match self {
+            Control::Unknown(unk) => [unk[0], unk[1]],
+            Control::MidRow(midrow) => midrow.to_bytes(),
+            Control::PreambleAddress(preamble) => preamble.to_bytes(),
+            _ => {
+                if let Ok(idx) =
+                    CONTROL_MAP_TABLE.binary_search_by_key(self, |control_map| control_map.control)
+                {
+                    CONTROL_MAP_TABLE[idx].cea608_bytes
+                } else {
+                    unreachable!();
+                }
+            }
+        };
+        (data[0], data[1])
+    }
+
+    /// The [`Mode`](crate::Mode) that this [`Control`] selects, if any
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{Mode, tables::Control};
+    /// assert_eq!(Control::ResumeCaptionLoading.to_mode(), Some(Mode::PopOn));
+    /// assert_eq!(Control::EndOfCaption.to_mode(), None);
+    /// ```
+    pub fn to_mode(&self) -> Option<crate::Mode> {
+        match self {
+            Control::ResumeCaptionLoading => Some(crate::Mode::PopOn),
+            Control::ResumeDirectionCaptioning => Some(crate::Mode::PaintOn),
+            Control::RollUp2 => Some(crate::Mode::RollUp2),
+            Control::RollUp3 => Some(crate::Mode::RollUp3),
+            Control::RollUp4 => Some(crate::Mode::RollUp4),
+            _ => None,
+        }
+    }
+
     /// Construct a new tab offset control code.
     pub fn tab_offset(offset: u8) -> Option<Control> {
         match offset {
             1 => Some(Control::TabOffset1),
-            2 => Some(Control::TabOffset1),
-            3 => Some(Control::TabOffset1),
+            2 => Some(Control::TabOffset2),
+            3 => Some(Control::TabOffset3),
             _ => None,
         }
     }
@@ -467,6 +560,66 @@ impl PreambleAddressCode {
         }
     }
 
+    /// Construct a preamble that places the cursor at an arbitrary position.
+    ///
+    /// Preamble indents are only available in multiples of 4 columns.  The closest preceding
+    /// indent is used and the remaining columns are returned as a tab offset [`Control`] code
+    /// that must be sent after the preamble.
+    ///
+    /// Returns None if the row is not in the range [0, 14] or the column is not in the range
+    /// [0, 31].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Control, PreambleAddressCode, PreambleType};
+    /// let (preamble, tab) = PreambleAddressCode::for_position(14, 6).unwrap();
+    /// assert_eq!(preamble.row(), 14);
+    /// assert_eq!(preamble.code(), PreambleType::Indent4);
+    /// assert_eq!(tab, Some(Control::TabOffset2));
+    /// ```
+    pub fn for_position(row: u8, column: u8) -> Option<(Self, Option<Control>)> {
+        if row > 14 {
+            return None;
+        }
+        let ty = PreambleType::from_indent(column - column % 4)?;
+        let preamble = Self::new(row, false, ty);
+        Some((preamble, Control::tab_offset(column % 4)))
+    }
+
+    /// The home preamble placing the cursor at the start of the bottom row (row 14 when 0-indexed)
+    /// with white non-underlined text.  This is the default position of the roll-up modes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::PreambleAddressCode;
+    /// let home = PreambleAddressCode::home();
+    /// assert_eq!((home.row(), home.column()), (14, 0));
+    /// assert!(home.is_home());
+    /// ```
+    pub fn home() -> Self {
+        Self::new(14, false, PreambleType::Indent0)
+    }
+
+    /// Whether this preamble places the cursor at the home position of column 0 on the bottom row.
+    /// Underline is ignored.  See [`home`](Self::home).
+    pub fn is_home(&self) -> bool {
+        self.row == 14 && self.ty == PreambleType::Indent0
+    }
+
+    /// The default attributes of a row as a tuple of (color, underline, italics).
+    ///
+    /// Every row starts out white, without underline and without italics.  A preamble or a mid-row
+    /// code may change these attributes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Color, PreambleAddressCode};
+    /// assert_eq!(PreambleAddressCode::default_attributes(), (Color::White, false, false));
+    /// ```
+    pub fn default_attributes() -> (Color, bool, bool) {
+        (Color::White, false, false)
+    }
+
     /// The row specified in this preamble (0-indexed)
     pub fn row(&self) -> u8 {
         self.row
@@ -507,6 +660,59 @@ impl PreambleAddressCode {
         self.ty.color()
     }
 
+    /// Whether this preamble is an indent preamble that sets the column, see [`column`](Self::column).
+    ///
+    /// Indent preambles always use white non-italic text.  Other preambles select a color or
+    /// italics and place the cursor in column 0.
+    pub fn has_indent(&self) -> bool {
+        matches!(
+            self.ty,
+            PreambleType::Indent0
+                | PreambleType::Indent4
+                | PreambleType::Indent8
+                | PreambleType::Indent12
+                | PreambleType::Indent16
+                | PreambleType::Indent20
+                | PreambleType::Indent24
+                | PreambleType::Indent28
+        )
+    }
+
+    /// Whether two preambles select the same row and attributes, ignoring the column.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Color, PreambleAddressCode, PreambleType};
+    /// let indent = PreambleAddressCode::new(4, false, PreambleType::Indent8);
+    /// let white = PreambleAddressCode::new(4, false, PreambleType::Color(Color::White));
+    /// assert!(indent.eq_ignoring_indent(&white));
+    /// let red = PreambleAddressCode::new(4, false, PreambleType::Color(Color::Red));
+    /// assert!(!indent.eq_ignoring_indent(&red));
+    /// ```
+    pub fn eq_ignoring_indent(&self, other: &Self) -> bool {
+        self.row == other.row
+            && self.underline == other.underline
+            && self.color() == other.color()
+            && self.italics() == other.italics()
+    }
+
+    /// The two bytes of this preamble for channel 1 without parity, e.g. for comparing against the
+    /// tables in the specification.  For channel 2, the first byte has `0x08` set.
+    ///
+    /// The bytes are laid out as `(0b0001_0rrr, 0b01Rt_tttu)`.  The `rrr` bits and the `R` bit
+    /// together select the row, `tttt` is the [`PreambleType`] and `u` signals underline.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{PreambleAddressCode, PreambleType};
+    /// let preamble = PreambleAddressCode::new(14, true, PreambleType::Indent4);
+    /// assert_eq!(preamble.raw_bits(), (0x14, 0x73));
+    /// ```
+    pub fn raw_bits(&self) -> (u8, u8) {
+        let [byte0, byte1] = self.to_bytes();
+        (byte0, byte1)
+    }
+
     fn to_bytes(self) -> [u8; 2] {
         let underline = if self.underline { 0x1 } else { 0x0 };
         let (row0, row1) = match self.row {
@@ -621,6 +827,20 @@ impl PreambleType {
     }
 }
 
+/// The character set or command group of a [`Code`].  See [`Code::category`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeCategory {
+    /// A single byte character from the basic character set
+    BasicLatin,
+    /// A character from the special character set, e.g. '♪'
+    Special,
+    /// A character from the extended Western European character sets that replaces the previous
+    /// character.  See [`Code::needs_backspace`].
+    ExtendedWesternEuropean,
+    /// A command, padding or an unknown byte
+    Control,
+}
+
 /// Enum of all possible characters or commands available
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 // must be ordered the same as the byte values for binary search to be successful
@@ -996,10 +1216,10 @@ static CONTROL_MAP_TABLE: [ControlMap; 99] = [
     ),
     control_map_bytes!([0x12, 0x25], Control::LatinLowerUWithDiaeseresis, Some('ü')),
     control_map_bytes!([0x12, 0x26], Control::OpeningSingleQuote, Some('´')),
-    control_map_bytes!([0x12, 0x27], Control::InvertedExclamationMark, Some('´')),
+    control_map_bytes!([0x12, 0x27], Control::InvertedExclamationMark, Some('¡')),
     control_map_bytes!([0x12, 0x28], Control::Asterisk, Some('*')),
     control_map_bytes!([0x12, 0x29], Control::SingleOpenQuote, Some('\'')),
-    control_map_bytes!([0x12, 0x2a], Control::EmDash, Some('_')),
+    control_map_bytes!([0x12, 0x2a], Control::EmDash, Some('—')),
     control_map_bytes!([0x12, 0x2b], Control::CopyrightSign, Some('Ⓒ')),
     control_map_bytes!([0x12, 0x2c], Control::ServiceMarkSign, Some('℠')),
     control_map_bytes!([0x12, 0x2d], Control::RoundBullet, None),
@@ -1098,7 +1318,7 @@ fn strip_parity(byte: u8) -> u8 {
     byte & 0x7F
 }
 
-fn add_parity(byte: u8) -> u8 {
+pub(crate) fn add_parity(byte: u8) -> u8 {
     debug_assert!((byte & 0x80) == 0);
     if check_odd_parity(byte) {
         byte
@@ -1226,6 +1446,84 @@ fn parse_preamble(byte0: u8, byte1: u8) -> Option<PreambleAddressCode> {
     Some(PreambleAddressCode { row, underline, ty })
 }
 
+/// Whether two byte pairs decode to the same [Code]s, ignoring any differences in parity
+///
+/// Both byte pairs are decoded after correcting their parity with [`fix_parity`].  Byte pairs
+/// that cannot be decoded are only equal if they are identical.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::tables::decode_eq;
+/// assert!(decode_eq([0xC1, 0x80], [0xC1, 0x80]));
+/// assert!(!decode_eq([0xC1, 0x80], [0xC2, 0x80]));
+/// // 'A' with and without parity
+/// assert!(decode_eq([0xC1, 0x80], [0x41, 0x00]));
+/// ```
+pub fn decode_eq(a: [u8; 2], b: [u8; 2]) -> bool {
+    match (
+        Code::from_data(fix_parity(a)),
+        Code::from_data(fix_parity(b)),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(_), Err(_)) => a == b,
+        _ => false,
+    }
+}
+
+/// Every character that can be encoded as a [Code], sorted and without duplicates.
+///
+/// This includes the basic, special and extended character sets.  See [`Code::from_char`].
+///
+/// # Examples
+/// ```
+/// # use cea608_types::tables::supported_chars;
+/// let chars = supported_chars().collect::<Vec<_>>();
+/// assert!(chars.contains(&'A'));
+/// assert!(chars.contains(&'♪'));
+/// assert!(!chars.contains(&'€'));
+/// ```
+pub fn supported_chars() -> impl Iterator<Item = char> {
+    CODE_MAP_TABLE
+        .iter()
+        .filter_map(|code_map| code_map.utf8)
+        .chain(
+            CONTROL_MAP_TABLE
+                .iter()
+                .filter_map(|control_map| control_map.utf8),
+        )
+        .filter(|c| Code::from_char(*c, Channel::ONE).is_some())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+}
+
+/// Whether both bytes of a byte pair have valid odd parity
+///
+/// # Examples
+/// ```
+/// # use cea608_types::tables::has_valid_parity;
+/// assert!(has_valid_parity([0x94, 0x2c]));
+/// assert!(!has_valid_parity([0x14, 0x2c]));
+/// ```
+pub fn has_valid_parity(data: [u8; 2]) -> bool {
+    check_odd_parity(data[0]) && check_odd_parity(data[1])
+}
+
+/// Replace the parity bit of both bytes of a byte pair with the correct odd parity.  The 7 data
+/// bits are kept unchanged.
+///
+/// # Examples
+/// ```
+/// # use cea608_types::tables::{fix_parity, has_valid_parity};
+/// assert_eq!(fix_parity([0x14, 0xac]), [0x94, 0x2c]);
+/// assert!(has_valid_parity(fix_parity([0x00, 0xff])));
+/// ```
+pub fn fix_parity(data: [u8; 2]) -> [u8; 2] {
+    [
+        add_parity(strip_parity(data[0])),
+        add_parity(strip_parity(data[1])),
+    ]
+}
+
 impl Code {
     /// The length in bytes of this [Code]
     ///
@@ -1234,9 +1532,9 @@ impl Code {
     /// # use cea608_types::tables::Code;
     /// assert_eq!(Code::LatinCapitalA.byte_len(), 1);
     /// ```
-    pub fn byte_len(&self) -> usize {
+    pub const fn byte_len(&self) -> usize {
         match self {
-            Code::Control(_) => 2,
+            Code::Control(control_code) => control_code.byte_len(),
             _ => 1,
         }
     }
@@ -1335,7 +1633,7 @@ impl Code {
                         .cea608_bytes
                         .iter()
                         .map(|b| add_parity(*b))
-                        .chain([0x80, 0x80].into_iter())
+                        .chain([0x80, 0x80])
                         .enumerate()
                         .take(2)
                     {
@@ -1348,6 +1646,59 @@ impl Code {
         unreachable!()
     }
 
+    /// Write a [Code] to the start of a slice of bytes, returning the number of bytes written.
+    ///
+    /// Unlike [`write_into`](Self::write_into), no padding is written after single byte [Code]s.
+    /// If `bytes` is too small to contain this [Code], then
+    /// [`WriterError::WouldOverflow`](crate::WriterError::WouldOverflow) is returned with the
+    /// number of missing bytes and `bytes` is not modified.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::{WriterError, tables::{Channel, Code}};
+    /// let mut written = [0; 1];
+    /// assert_eq!(Code::LatinCapitalC.write_into_slice(&mut written), Ok(1));
+    /// assert_eq!(written, [0x43]);
+    /// let degree = Code::from_char('°', Channel::ONE).unwrap();
+    /// assert_eq!(degree.write_into_slice(&mut written), Err(WriterError::WouldOverflow(1)));
+    /// ```
+    pub fn write_into_slice(&self, bytes: &mut [u8]) -> Result<usize, crate::WriterError> {
+        let len = self.byte_len();
+        if bytes.len() < len {
+            return Err(crate::WriterError::WouldOverflow(len - bytes.len()));
+        }
+        let mut data = [0; 2];
+        self.write_into(&mut data);
+        bytes[..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+
+    /// The bytes of this [Code] without parity applied
+    ///
+    /// Unlike [`write_into`](Self::write_into), the returned values are the 7-bit values as used
+    /// in the CEA-608 specification tables.  The second byte is only present for [Code]s that
+    /// are two bytes long.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Channel, Code, Control, ControlCode, Field};
+    /// assert_eq!(Code::LatinCapitalA.to_normalized_bytes(), (0x41, None));
+    /// let eoc = Code::Control(ControlCode::new(Field::ONE, Channel::ONE, Control::EndOfCaption));
+    /// assert_eq!(eoc.to_normalized_bytes(), (0x14, Some(0x2f)));
+    /// ```
+    pub fn to_normalized_bytes(&self) -> (u8, Option<u8>) {
+        let mut data = [0; 2];
+        let len = self.write_into(&mut data);
+        (
+            strip_parity(data[0]),
+            if len > 1 {
+                Some(strip_parity(data[1]))
+            } else {
+                None
+            },
+        )
+    }
+
     /// The utf8 char for this [Code]
     ///
     /// [Code]s that represent a command will return None.
@@ -1379,6 +1730,21 @@ impl Code {
         })
     }
 
+    /// Retrieve a [Code] for a Unicode codepoint.  See [`from_char`](Self::from_char).
+    ///
+    /// If `codepoint` is not a valid Unicode scalar value or the char is not representable as a
+    /// [Code], None will be returned.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Code, Channel};
+    /// assert_eq!(Code::from_codepoint(0x41, Channel::ONE), Some(Code::LatinCapitalA));
+    /// assert_eq!(Code::from_codepoint(0xd800, Channel::ONE), None);
+    /// ```
+    pub fn from_codepoint(codepoint: u32, channel: Channel) -> Option<Code> {
+        Self::from_char(char::from_u32(codepoint)?, channel)
+    }
+
     /// Retrieve a [Code] for a utf8 char
     ///
     /// If the char is not representable as a [Code], None will be returned.
@@ -1391,12 +1757,18 @@ impl Code {
     pub fn from_char(c: char, channel: Channel) -> Option<Code> {
         // table is not currently sorted by utf8 value so cannot binary search through it.  May
         // need another lookup table if this is a performance concern
-        CODE_MAP_TABLE.iter().find_map(|code_map| {
-            if code_map.utf8 == Some(c) {
-                Some(code_map.code)
-            } else {
+        CODE_MAP_TABLE
+            .iter()
+            .find_map(|code_map| {
+                if code_map.utf8 == Some(c) {
+                    Some(code_map.code)
+                } else {
+                    None
+                }
+            })
+            .or_else(|| {
                 CONTROL_MAP_TABLE.iter().find_map(|control_map| {
-                    if code_map.utf8 == Some(c) {
+                    if control_map.utf8 == Some(c) {
                         Some(Code::Control(ControlCode {
                             field: None,
                             channel,
@@ -1406,8 +1778,159 @@ impl Code {
                         None
                     }
                 })
-            }
-        })
+            })
+    }
+
+    /// Retrieve a [Code] for an ASCII byte
+    ///
+    /// Only the printable ASCII characters that are part of the CEA-608 basic character set are
+    /// supported.  Any other byte, including the ASCII characters that the basic character set
+    /// replaces with accented characters (e.g. `*` or `\`), will return None.
+    ///
+    /// This function can be used in const contexts.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::Code;
+    /// const A: Option<Code> = Code::from_ascii(b'A');
+    /// assert_eq!(A, Some(Code::LatinCapitalA));
+    /// assert_eq!(Code::from_ascii(b'*'), None);
+    /// ```
+    pub const fn from_ascii(b: u8) -> Option<Code> {
+        match b {
+            b' ' => Some(Code::Space),
+            b'!' => Some(Code::ExclamationMark),
+            b'"' => Some(Code::QuotationMark),
+            b'#' => Some(Code::NumberSign),
+            b'$' => Some(Code::DollarSign),
+            b'%' => Some(Code::PercentSign),
+            b'&' => Some(Code::Ampersand),
+            b'\'' => Some(Code::Apostrophe),
+            b'(' => Some(Code::LeftParenthesis),
+            b')' => Some(Code::RightParenthesis),
+            b'+' => Some(Code::PlusSign),
+            b',' => Some(Code::Comma),
+            b'-' => Some(Code::HyphenMinus),
+            b'.' => Some(Code::FullStop),
+            b'/' => Some(Code::Solidus),
+            b'0' => Some(Code::Zero),
+            b'1' => Some(Code::One),
+            b'2' => Some(Code::Two),
+            b'3' => Some(Code::Three),
+            b'4' => Some(Code::Four),
+            b'5' => Some(Code::Five),
+            b'6' => Some(Code::Six),
+            b'7' => Some(Code::Seven),
+            b'8' => Some(Code::Eight),
+            b'9' => Some(Code::Nine),
+            b':' => Some(Code::Colon),
+            b';' => Some(Code::SemiColon),
+            b'<' => Some(Code::LessThan),
+            b'=' => Some(Code::Equals),
+            b'>' => Some(Code::GreaterThan),
+            b'?' => Some(Code::QuestionMark),
+            b'@' => Some(Code::CommercialAt),
+            b'A' => Some(Code::LatinCapitalA),
+            b'B' => Some(Code::LatinCapitalB),
+            b'C' => Some(Code::LatinCapitalC),
+            b'D' => Some(Code::LatinCapitalD),
+            b'E' => Some(Code::LatinCapitalE),
+            b'F' => Some(Code::LatinCapitalF),
+            b'G' => Some(Code::LatinCapitalG),
+            b'H' => Some(Code::LatinCapitalH),
+            b'I' => Some(Code::LatinCapitalI),
+            b'J' => Some(Code::LatinCapitalJ),
+            b'K' => Some(Code::LatinCapitalK),
+            b'L' => Some(Code::LatinCapitalL),
+            b'M' => Some(Code::LatinCapitalM),
+            b'N' => Some(Code::LatinCapitalN),
+            b'O' => Some(Code::LatinCapitalO),
+            b'P' => Some(Code::LatinCapitalP),
+            b'Q' => Some(Code::LatinCapitalQ),
+            b'R' => Some(Code::LatinCapitalR),
+            b'S' => Some(Code::LatinCapitalS),
+            b'T' => Some(Code::LatinCapitalT),
+            b'U' => Some(Code::LatinCapitalU),
+            b'V' => Some(Code::LatinCapitalV),
+            b'W' => Some(Code::LatinCapitalW),
+            b'X' => Some(Code::LatinCapitalX),
+            b'Y' => Some(Code::LatinCapitalY),
+            b'Z' => Some(Code::LatinCapitalZ),
+            b'[' => Some(Code::LeftSquareBracket),
+            b']' => Some(Code::RightSquareBracket),
+            b'a' => Some(Code::LatinLowerA),
+            b'b' => Some(Code::LatinLowerB),
+            b'c' => Some(Code::LatinLowerC),
+            b'd' => Some(Code::LatinLowerD),
+            b'e' => Some(Code::LatinLowerE),
+            b'f' => Some(Code::LatinLowerF),
+            b'g' => Some(Code::LatinLowerG),
+            b'h' => Some(Code::LatinLowerH),
+            b'i' => Some(Code::LatinLowerI),
+            b'j' => Some(Code::LatinLowerJ),
+            b'k' => Some(Code::LatinLowerK),
+            b'l' => Some(Code::LatinLowerL),
+            b'm' => Some(Code::LatinLowerM),
+            b'n' => Some(Code::LatinLowerN),
+            b'o' => Some(Code::LatinLowerO),
+            b'p' => Some(Code::LatinLowerP),
+            b'q' => Some(Code::LatinLowerQ),
+            b'r' => Some(Code::LatinLowerR),
+            b's' => Some(Code::LatinLowerS),
+            b't' => Some(Code::LatinLowerT),
+            b'u' => Some(Code::LatinLowerU),
+            b'v' => Some(Code::LatinLowerV),
+            b'w' => Some(Code::LatinLowerW),
+            b'x' => Some(Code::LatinLowerX),
+            b'y' => Some(Code::LatinLowerY),
+            b'z' => Some(Code::LatinLowerZ),
+            _ => None,
+        }
+    }
+
+    /// Whether displaying this [Code] moves the cursor one column to the right.
+    ///
+    /// All CEA-608 characters are a single column wide.  Characters that need a backspace replace
+    /// the previous character and do not move the cursor.  Mid-row codes are displayed as a space
+    /// and do move the cursor.  Other control codes and padding do not move the cursor.
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::Code;
+    /// assert!(Code::LatinCapitalA.advances_cursor());
+    /// assert!(!Code::NUL.advances_cursor());
+    /// ```
+    pub fn advances_cursor(&self) -> bool {
+        match self {
+            Code::NUL | Code::Unknown(_) => false,
+            Code::Control(ControlCode { control, .. }) => match control {
+                Control::MidRow(_) | Control::TransparentSpace => true,
+                _ => !self.needs_backspace() && self.char().is_some(),
+            },
+            _ => true,
+        }
+    }
+
+    /// The [`CodeCategory`] of this [Code]
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Code, CodeCategory};
+    /// assert_eq!(Code::LatinCapitalA.category(), CodeCategory::BasicLatin);
+    /// assert_eq!(Code::NUL.category(), CodeCategory::Control);
+    /// ```
+    pub fn category(&self) -> CodeCategory {
+        match self {
+            Code::NUL | Code::Unknown(_) => CodeCategory::Control,
+            Code::Control(_) if self.needs_backspace() => CodeCategory::ExtendedWesternEuropean,
+            Code::Control(ControlCode {
+                control: Control::TransparentSpace,
+                ..
+            }) => CodeCategory::Special,
+            Code::Control(_) if self.char().is_some() => CodeCategory::Special,
+            Code::Control(_) => CodeCategory::Control,
+            _ => CodeCategory::BasicLatin,
+        }
     }
 
     /// Whether or not this code requires there to have a backspace prepended for correct display
@@ -1420,10 +1943,10 @@ impl Code {
         else {
             return false;
         };
+        // mid-row codes are displayed as a space and do not replace the previous character
         matches!(
             control,
-            Control::MidRow(_)
-            | Control::LatinCapitalAWithAcute
+            Control::LatinCapitalAWithAcute
             | Control::LatinCapitalEWithAcute
             | Control::LatinCapitalOWithAcute
             | Control::LatinCapitalUWithAcute
@@ -1496,10 +2019,49 @@ impl Code {
     }
 }
 
+impl TryFrom<[u8; 2]> for Code {
+    type Error = CodeError;
+
+    /// Parse a byte pair containing a single [Code] using [`Code::from_data`].
+    ///
+    /// A byte pair containing two single byte [Code]s where the second is not [`Code::NUL`] will
+    /// return [`CodeError::LengthMismatch`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use cea608_types::tables::{Code, CodeError};
+    /// let code: Code = [0xC1, 0x80].try_into().unwrap();
+    /// assert_eq!(code, Code::LatinCapitalA);
+    /// assert_eq!(
+    ///     Code::try_from([0xC1, 0xC2]),
+    ///     Err(CodeError::LengthMismatch { expected: 1, actual: 2 })
+    /// );
+    /// ```
+    fn try_from(data: [u8; 2]) -> Result<Self, Self::Error> {
+        match Code::from_data(data)? {
+            [code @ Code::Control(_), _] | [code, Code::NUL] => Ok(code),
+            _ => Err(CodeError::LengthMismatch {
+                expected: 1,
+                actual: 2,
+            }),
+        }
+    }
+}
+
+impl From<Code> for [u8; 2] {
+    /// Write a [Code] into a byte pair using [`Code::write_into`]
+    fn from(code: Code) -> Self {
+        let mut data = [0; 2];
+        code.write_into(&mut data);
+        data
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::tests::*;
+    use crate::WriterError;
 
     #[test]
     fn codes_table_ordered() {
@@ -1561,6 +2123,378 @@ mod test {
         }
     }
 
+    #[test]
+    fn codes_from_ascii() {
+        test_init_log();
+        for b in 0..=0xff {
+            if let Some(code) = Code::from_ascii(b) {
+                assert_eq!(code.char(), Some(b as char));
+                assert_eq!(code.byte_len(), 1);
+            } else if (0x20..0x7f).contains(&b) {
+                assert!(Code::from_char(b as char, Channel::ONE)
+                    .map_or(true, |code| matches!(code, Code::Control(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn codes_advance_cursor() {
+        test_init_log();
+        let control = |control| Code::Control(ControlCode::new(Field::ONE, Channel::ONE, control));
+        assert!(Code::Space.advances_cursor());
+        assert!(control(Control::MusicalNote).advances_cursor());
+        assert!(control(Control::TransparentSpace).advances_cursor());
+        assert!(control(Control::MidRow(MidRow::new_italics(true))).advances_cursor());
+        assert!(!control(Control::MidRow(MidRow::new_italics(true))).needs_backspace());
+        assert!(!control(Control::LatinCapitalAWithAcute).advances_cursor());
+        assert!(!control(Control::CarriageReturn).advances_cursor());
+        assert!(!Code::Unknown(0x7f).advances_cursor());
+    }
+
+    #[test]
+    fn codes_category() {
+        test_init_log();
+        let control = |control| Code::Control(ControlCode::new(Field::ONE, Channel::ONE, control));
+        for (code, category) in [
+            (Code::Space, CodeCategory::BasicLatin),
+            (Code::LatinLowerAWithAcute, CodeCategory::BasicLatin),
+            (control(Control::MusicalNote), CodeCategory::Special),
+            (control(Control::TransparentSpace), CodeCategory::Special),
+            (
+                control(Control::LatinCapitalEWithAcute),
+                CodeCategory::ExtendedWesternEuropean,
+            ),
+            (
+                control(Control::CopyrightSign),
+                CodeCategory::ExtendedWesternEuropean,
+            ),
+            (control(Control::EndOfCaption), CodeCategory::Control),
+            (
+                control(Control::MidRow(MidRow::new_italics(false))),
+                CodeCategory::Control,
+            ),
+            (Code::NUL, CodeCategory::Control),
+            (Code::Unknown(0x05), CodeCategory::Control),
+        ] {
+            assert_eq!(code.category(), category, "{code:?}");
+        }
+    }
+
+    #[test]
+    fn preamble_for_position() {
+        test_init_log();
+        for (column, ty, tab) in [
+            (0, PreambleType::Indent0, None),
+            (4, PreambleType::Indent4, None),
+            (6, PreambleType::Indent4, Some(Control::TabOffset2)),
+            (31, PreambleType::Indent28, Some(Control::TabOffset3)),
+        ] {
+            let (preamble, tab_offset) = PreambleAddressCode::for_position(3, column).unwrap();
+            assert_eq!(preamble.row(), 3);
+            assert_eq!(preamble.code(), ty);
+            assert!(!preamble.underline());
+            assert_eq!(tab_offset, tab);
+        }
+        assert_eq!(PreambleAddressCode::for_position(15, 0), None);
+        assert_eq!(PreambleAddressCode::for_position(0, 32), None);
+    }
+
+    #[test]
+    fn codes_decode_eq() {
+        test_init_log();
+        assert!(decode_eq([0x94, 0x2c], [0x94, 0x2c]));
+        assert!(!decode_eq([0x94, 0x2c], [0x94, 0x2f]));
+        // invalid parity
+        assert!(decode_eq([0x41, 0x80], [0x41, 0x80]));
+        assert!(!decode_eq([0x41, 0x80], [0x42, 0x80]));
+        assert!(decode_eq([0x41, 0x80], [0xc1, 0x80]));
+        assert!(decode_eq([0x14, 0x2c], [0x94, 0x2c]));
+        assert!(!decode_eq([0x14, 0x2c], [0x94, 0x2f]));
+    }
+
+    #[test]
+    fn controls_to_from_char() {
+        test_init_log();
+        for control_map in CONTROL_MAP_TABLE.iter() {
+            trace!("parsing {control_map:?}");
+            if let Some(c) = control_map.utf8 {
+                let parsed_code = Code::from_char(c, Channel::TWO).unwrap();
+                assert_eq!(parsed_code.char(), control_map.utf8);
+                if let Code::Control(control) = parsed_code {
+                    assert_eq!(control.channel(), Channel::TWO);
+                }
+            }
+        }
+        assert_eq!(
+            Code::from_char('♪', Channel::ONE),
+            Some(Code::Control(ControlCode {
+                field: None,
+                channel: Channel::ONE,
+                control: Control::MusicalNote
+            }))
+        );
+    }
+
+    #[test]
+    fn preamble_indent() {
+        test_init_log();
+        let indent0 = PreambleAddressCode::new(2, true, PreambleType::Indent0);
+        let indent28 = PreambleAddressCode::new(2, true, PreambleType::Indent28);
+        let italics = PreambleAddressCode::new(2, true, PreambleType::WhiteItalics);
+        let green = PreambleAddressCode::new(2, true, PreambleType::Color(Color::Green));
+        assert!(indent0.has_indent());
+        assert!(indent28.has_indent());
+        assert!(!italics.has_indent());
+        assert!(!green.has_indent());
+        assert!(indent0.eq_ignoring_indent(&indent28));
+        assert!(!indent0.eq_ignoring_indent(&italics));
+        assert!(!indent0.eq_ignoring_indent(&green));
+        let other_row = PreambleAddressCode::new(3, true, PreambleType::Indent0);
+        assert!(!indent0.eq_ignoring_indent(&other_row));
+        let no_underline = PreambleAddressCode::new(2, false, PreambleType::Indent0);
+        assert!(!indent0.eq_ignoring_indent(&no_underline));
+    }
+
+    #[test]
+    fn control_code_byte_len() {
+        test_init_log();
+        for control in [
+            Control::MusicalNote,
+            Control::LatinCapitalEWithAcute,
+            Control::CarriageReturn,
+            Control::PreambleAddress(PreambleAddressCode::home()),
+        ] {
+            let control_code = ControlCode::new(Field::TWO, Channel::TWO, control);
+            assert_eq!(control_code.byte_len(), 2);
+            let code = Code::Control(control_code);
+            assert_eq!(code.byte_len(), control_code.byte_len());
+            let mut data = vec![];
+            code.write(&mut data).unwrap();
+            assert_eq!(data.len(), control_code.byte_len());
+        }
+        let mut data = vec![];
+        Code::LatinCapitalA.write(&mut data).unwrap();
+        assert_eq!(data.len(), Code::LatinCapitalA.byte_len());
+        assert_eq!(Code::LatinCapitalA.byte_len(), 1);
+    }
+
+    #[test]
+    fn preamble_home() {
+        test_init_log();
+        let home = PreambleAddressCode::home();
+        let code = Code::Control(ControlCode::new(
+            Field::ONE,
+            Channel::ONE,
+            Control::PreambleAddress(home),
+        ));
+        let mut data = vec![];
+        code.write(&mut data).unwrap();
+        assert_eq!(data, [0x94, 0x70]);
+        let [Code::Control(parsed), _] = Code::from_data([0x94, 0x70]).unwrap() else {
+            unreachable!();
+        };
+        assert_eq!(parsed.code(), Control::PreambleAddress(home));
+        assert!(home.is_home());
+        assert!(PreambleAddressCode::new(14, true, PreambleType::Indent0).is_home());
+        assert!(!PreambleAddressCode::new(13, false, PreambleType::Indent0).is_home());
+        assert!(!PreambleAddressCode::new(14, false, PreambleType::Indent4).is_home());
+        assert!(!PreambleAddressCode::new(14, false, PreambleType::Color(Color::White)).is_home());
+    }
+
+    #[test]
+    fn raw_bits() {
+        test_init_log();
+        for row in 0..15 {
+            for ty in [
+                PreambleType::Color(Color::Cyan),
+                PreambleType::WhiteItalics,
+                PreambleType::Indent12,
+            ] {
+                for underline in [false, true] {
+                    let preamble = PreambleAddressCode::new(row, underline, ty);
+                    let (byte0, byte1) = preamble.raw_bits();
+                    assert_eq!(
+                        parse_control_code([byte0, byte1]).code(),
+                        Control::PreambleAddress(preamble)
+                    );
+                }
+            }
+        }
+        for midrow in [
+            MidRow::new_color(Color::White, false),
+            MidRow::new_color(Color::Magenta, true),
+            MidRow::new_italics(true),
+        ] {
+            assert_eq!(
+                parse_control_code([0x11, midrow.raw_bits()]).code(),
+                Control::MidRow(midrow)
+            );
+        }
+    }
+
+    #[test]
+    fn preamble_white_italics() {
+        test_init_log();
+        // row 1, white italics with and without underline
+        for (byte1, underline) in [(0xce, false), (0x4f, true)] {
+            let parsed = Code::from_data([0x91, byte1]).unwrap();
+            let Code::Control(ControlCode {
+                control: Control::PreambleAddress(preamble),
+                ..
+            }) = parsed[0]
+            else {
+                unreachable!();
+            };
+            assert_eq!(preamble.code(), PreambleType::WhiteItalics);
+            assert!(preamble.italics());
+            assert_eq!(preamble.color(), Color::White);
+            assert_eq!(preamble.underline(), underline);
+            assert_eq!(preamble.column(), 0);
+        }
+    }
+
+    #[test]
+    fn codes_try_from_into() {
+        test_init_log();
+        let eoc = Code::Control(ControlCode::new(
+            Field::ONE,
+            Channel::ONE,
+            Control::EndOfCaption,
+        ));
+        let data: [u8; 2] = eoc.into();
+        assert_eq!(data, [0x94, 0x2f]);
+        assert_eq!(Code::try_from(data), Ok(eoc));
+        let data: [u8; 2] = Code::LatinLowerA.into();
+        assert_eq!(data, [0x61, 0x80]);
+        assert_eq!(Code::try_from(data), Ok(Code::LatinLowerA));
+        assert_eq!(Code::try_from([0x80, 0x80]), Ok(Code::NUL));
+        assert_eq!(Code::try_from([0x41, 0x80]), Err(CodeError::InvalidParity));
+    }
+
+    #[test]
+    fn control_valid_combination() {
+        test_init_log();
+        let eoc = ControlCode::new(Field::ONE, Channel::TWO, Control::EndOfCaption);
+        assert!(eoc.is_valid_combination());
+        let eoc = ControlCode { field: None, ..eoc };
+        assert!(!eoc.is_valid_combination());
+        let degree = ControlCode {
+            field: None,
+            channel: Channel::ONE,
+            control: Control::DegreeSign,
+        };
+        assert!(degree.is_valid_combination());
+        let unknown = ControlCode::new(Field::ONE, Channel::ONE, Control::Unknown([0x10, 0x20]));
+        assert!(!unknown.is_valid_combination());
+    }
+
+    #[test]
+    fn codes_write_into_slice_overflow() {
+        test_init_log();
+        let eoc = Code::Control(ControlCode::new(
+            Field::ONE,
+            Channel::ONE,
+            Control::EndOfCaption,
+        ));
+        let mut bytes = [0xff; 3];
+        assert_eq!(
+            eoc.write_into_slice(&mut bytes[..0]),
+            Err(WriterError::WouldOverflow(2))
+        );
+        assert_eq!(
+            eoc.write_into_slice(&mut bytes[..1]),
+            Err(WriterError::WouldOverflow(1))
+        );
+        assert_eq!(bytes, [0xff; 3]);
+        assert_eq!(eoc.write_into_slice(&mut bytes), Ok(2));
+        assert_eq!(bytes, [0x94, 0x2f, 0xff]);
+        assert_eq!(
+            Code::LatinLowerA.write_into_slice(&mut bytes[..0]),
+            Err(WriterError::WouldOverflow(1))
+        );
+        assert_eq!(Code::LatinLowerA.write_into_slice(&mut bytes[2..]), Ok(1));
+        assert_eq!(bytes, [0x94, 0x2f, 0x61]);
+        assert_eq!(Code::Unknown(0x05).write_into_slice(&mut bytes[..1]), Ok(1));
+        assert_eq!(bytes[0], 0x85);
+    }
+
+    #[test]
+    fn parity_fix() {
+        test_init_log();
+        for data in [[0xc1, 0x80], [0x94, 0x2c], [0x80, 0x80]] {
+            assert!(has_valid_parity(data));
+            assert_eq!(fix_parity(data), data);
+            for corrupted in [[data[0] ^ 0x80, data[1]], [data[0], data[1] ^ 0x80]] {
+                assert!(!has_valid_parity(corrupted));
+                assert_eq!(Code::from_data(corrupted), Err(CodeError::InvalidParity));
+                assert_eq!(fix_parity(corrupted), data);
+            }
+        }
+    }
+
+    #[test]
+    fn codes_supported_chars() {
+        test_init_log();
+        let chars = supported_chars().collect::<Vec<_>>();
+        assert!(chars.windows(2).all(|w| w[0] < w[1]));
+        for c in &chars {
+            let code = Code::from_char(*c, Channel::ONE).unwrap();
+            assert_eq!(code.char(), Some(*c));
+        }
+        for c in ['a', 'é', '½', 'Ö', '\\', '^'] {
+            assert!(chars.contains(&c));
+        }
+        assert!(!chars.contains(&'€'));
+    }
+
+    #[test]
+    fn codes_from_codepoint() {
+        test_init_log();
+        assert_eq!(
+            Code::from_codepoint('é' as u32, Channel::ONE),
+            Code::from_char('é', Channel::ONE)
+        );
+        assert_eq!(
+            Code::from_codepoint('™' as u32, Channel::TWO),
+            Code::from_char('™', Channel::TWO)
+        );
+        assert!(Code::from_codepoint('™' as u32, Channel::TWO).is_some());
+        // outside of the CEA-608 repertoire
+        assert_eq!(Code::from_codepoint('€' as u32, Channel::ONE), None);
+        // invalid codepoints
+        assert_eq!(Code::from_codepoint(0xdfff, Channel::ONE), None);
+        assert_eq!(Code::from_codepoint(0x110000, Channel::ONE), None);
+    }
+
+    #[test]
+    fn control_to_mode() {
+        test_init_log();
+        for (control, mode) in [
+            (Control::ResumeCaptionLoading, crate::Mode::PopOn),
+            (Control::ResumeDirectionCaptioning, crate::Mode::PaintOn),
+            (Control::RollUp2, crate::Mode::RollUp2),
+            (Control::RollUp3, crate::Mode::RollUp3),
+            (Control::RollUp4, crate::Mode::RollUp4),
+        ] {
+            assert_eq!(control.to_mode(), Some(mode));
+        }
+        assert_eq!(Control::ResumeTextDisplay.to_mode(), None);
+        assert_eq!(Control::CarriageReturn.to_mode(), None);
+    }
+
+    #[test]
+    fn control_to_code_bytes() {
+        test_init_log();
+        for control_map in CONTROL_MAP_TABLE.iter() {
+            let (byte0, byte1) = control_map.control.to_code_bytes();
+            assert_eq!([byte0, byte1], control_map.cea608_bytes);
+            let data = ControlCode::new(Field::ONE, Channel::ONE, control_map.control).write();
+            assert_eq!([data[0] & 0x7f, data[1] & 0x7f], [byte0, byte1]);
+        }
+        let midrow = Control::MidRow(MidRow::new_color(Color::Red, true));
+        assert_eq!(midrow.to_code_bytes(), (0x11, 0x29));
+    }
+
     #[test]
     fn preamble_to_from_bytes() {
         test_init_log();